@@ -14,9 +14,10 @@ async fn main() {
     let client = Client::new();
     let ranks = client.ranks("google.com").await;
     dbg!(&ranks);
-    let list = client.list("LJL44").await;
+    let id = "LJL44".parse().unwrap();
+    let list = client.list(&id).await;
     dbg!(&list);
-    let list2 = client.list_date(2025, 04, 07, Some(false)).await;
+    let list2 = client.list_date(2025, 4, 7, Some(false)).await;
     dbg!(&list2);
     let downloaded_list = client.download_list(&list2.unwrap()).await;
     dbg!(&downloaded_list);
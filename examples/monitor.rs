@@ -0,0 +1,36 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+use std::time::Duration;
+use tranco::Client;
+
+/// Rank change that triggers an alert
+const ALERT_THRESHOLD: i64 = 1000;
+
+#[tokio::main]
+async fn main() {
+    let client = Client::new();
+    let mut previous: Option<u64> = None;
+    loop {
+        match client.poll_rank("google.com").await {
+            Ok(rank) => {
+                println!("{}: {}", rank.date, rank.rank);
+                if let Some(previous) = previous {
+                    let change = rank.rank as i64 - previous as i64;
+                    if change.abs() >= ALERT_THRESHOLD {
+                        println!("rank moved by {change}");
+                    }
+                }
+                previous = Some(rank.rank);
+            }
+            Err(e) => eprintln!("{e}"),
+        }
+        tokio::time::sleep(Duration::from_secs(24 * 60 * 60)).await;
+    }
+}
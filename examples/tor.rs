@@ -0,0 +1,31 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+use tranco::Client;
+
+/// SOCKS port of a local Tor daemon
+///
+/// `socks5h` makes Tor resolve hostnames, so DNS lookups don't leak outside the circuit.
+const TOR_PROXY: &str = "socks5h://127.0.0.1:9050";
+
+#[tokio::main]
+async fn main() {
+    // Tor isolates streams by SOCKS credentials, so these keep this client on its own circuit
+    let proxy = reqwest::Proxy::all(TOR_PROXY)
+        .expect("invalid proxy URL")
+        .basic_auth("tranco", "measurement-1");
+    let client = Client::builder().proxy(proxy).build();
+    let ranks = client
+        .ranks("torproject.org")
+        .await
+        .expect("failed to fetch ranks");
+    for rank in ranks.ranks {
+        println!("{}: {}", rank.date, rank.rank);
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    ApiError, Client, DOWNLOAD_CONCURRENCY, ListDateQuery, RankedDomain, days, parse_line,
+};
+use chrono::NaiveDate;
+use futures_util::{Stream, StreamExt, stream};
+use std::collections::HashMap;
+
+impl Client {
+    /// Compute each domain's mean rank across the daily lists within a date range
+    ///
+    /// Only the dates on which a domain appears count towards its mean. Lists are downloaded one
+    /// at a time and every line is folded into per-domain running totals as it is read, so memory
+    /// grows with the number of distinct domains rather than with the size or number of lists.
+    ///
+    /// # Parameters
+    /// * `start` - first date of the range (inclusive)
+    /// * `end` - last date of the range (inclusive)
+    /// * `require_all_dates` - only include domains present in every list in the range
+    pub async fn average_ranks(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        require_all_dates: bool,
+    ) -> Result<HashMap<String, f64>, ApiError> {
+        let mut totals: HashMap<String, (u64, usize)> = HashMap::new();
+        let dates = self
+            .fold_daily_lists(start, end, |ranked| {
+                let (sum, count) = totals.entry(ranked.domain).or_default();
+                *sum += ranked.rank;
+                *count += 1;
+            })
+            .await?;
+        Ok(totals
+            .into_iter()
+            .filter(|(_, (_, count))| !require_all_dates || *count == dates)
+            .map(|(domain, (sum, count))| (domain, sum as f64 / count as f64))
+            .collect())
+    }
+    /// Compute each domain's median rank across the daily lists within a date range
+    ///
+    /// Only the dates on which a domain appears count towards its median, and the median of an
+    /// even number of ranks is the mean of the middle two. Lists are streamed line by line like
+    /// with `average_ranks`, but a median can't be computed from running totals: every rank of
+    /// every domain has to be kept until the last list is read, so memory grows with the number
+    /// of lists in the range.
+    ///
+    /// # Parameters
+    /// * `start` - first date of the range (inclusive)
+    /// * `end` - last date of the range (inclusive)
+    /// * `require_all_dates` - only include domains present in every list in the range
+    pub async fn median_ranks(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        require_all_dates: bool,
+    ) -> Result<HashMap<String, f64>, ApiError> {
+        let mut all_ranks: HashMap<String, Vec<u64>> = HashMap::new();
+        let dates = self
+            .fold_daily_lists(start, end, |ranked| {
+                all_ranks
+                    .entry(ranked.domain)
+                    .or_default()
+                    .push(ranked.rank);
+            })
+            .await?;
+        Ok(all_ranks
+            .into_iter()
+            .filter(|(_, ranks)| !require_all_dates || ranks.len() == dates)
+            .map(|(domain, mut ranks)| {
+                ranks.sort_unstable();
+                let mid = ranks.len() / 2;
+                let median = if ranks.len() % 2 == 0 {
+                    (ranks[mid - 1] + ranks[mid]) as f64 / 2.0
+                } else {
+                    ranks[mid] as f64
+                };
+                (domain, median)
+            })
+            .collect())
+    }
+    /// Download every daily list within a date range, yielding each as soon as it completes
+    ///
+    /// Up to `DOWNLOAD_CONCURRENCY` lists are fetched at a time. Items arrive in completion order
+    /// rather than date order, each tagged with its date, and a failure for one date doesn't end
+    /// the stream.
+    ///
+    /// # Parameters
+    /// * `start` - first date of the range (inclusive)
+    /// * `end` - last date of the range (inclusive)
+    /// * `subdomains` - whether the lists should include subdomains, or `None` for the API default
+    pub fn download_range_stream(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        subdomains: Option<bool>,
+    ) -> impl Stream<Item = (NaiveDate, Result<Vec<RankedDomain>, ApiError>)> + '_ {
+        stream::iter(days(start, end))
+            .map(move |date| async move {
+                let query = ListDateQuery::new(date).maybe_subdomains(subdomains);
+                let list = async {
+                    let list = self.list_date_query(&query).await?;
+                    self.download_list(&list).await
+                };
+                (date, list.await)
+            })
+            .buffer_unordered(DOWNLOAD_CONCURRENCY)
+    }
+    /// Download every daily list within a date range, handing each of their domains to `f` as
+    /// it is read
+    ///
+    /// Lists are downloaded one after the other, sharing one buffer for partial lines, so only a
+    /// chunk of one body is held in memory at a time.
+    ///
+    /// # Returns
+    /// The number of lists processed
+    async fn fold_daily_lists(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        mut f: impl FnMut(RankedDomain),
+    ) -> Result<usize, ApiError> {
+        let mut buffer = Vec::new();
+        let mut dates = 0;
+        for date in days(start, end) {
+            let list = self.list_on(date).await?;
+            self.for_each_chunk_line_in(
+                &mut buffer,
+                &list,
+                |_| (),
+                |line| {
+                    f(parse_line(line)?);
+                    Ok(())
+                },
+            )
+            .await?;
+            dates += 1;
+        }
+        Ok(dates)
+    }
+}
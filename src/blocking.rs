@@ -0,0 +1,102 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+//! Blocking client, for use outside of an async runtime
+//!
+//! Each `Client` wraps an async `crate::Client` and drives it on its own single-threaded tokio
+//! runtime, so callers don't need to set one up. Like `reqwest::blocking`, it must not be used
+//! from within an async runtime.
+
+use crate::{ApiError, Error, ListId, ListsResponse, RankedDomain, RanksResponse};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+
+/// Blocking client used to make Tranco API calls
+///
+/// Cloning is cheap, and clones share the runtime and the underlying async client.
+#[derive(Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl From<crate::Client> for Client {
+    fn from(client: crate::Client) -> Self {
+        Self::from_async(client)
+    }
+}
+impl Client {
+    /// Constructor
+    ///
+    /// # Panics
+    /// Panics if the runtime can't be created
+    pub fn new() -> Self {
+        Self::from_async(crate::Client::new())
+    }
+    /// Constructor from an async client, e.g. one configured with `crate::Client::builder`
+    ///
+    /// # Panics
+    /// Panics if the runtime can't be created
+    pub fn from_async(client: crate::Client) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the runtime of the blocking client");
+        Self {
+            inner: client,
+            runtime: Arc::new(runtime),
+        }
+    }
+    /// Run a call of the async client to completion
+    fn block_on<T>(&self, call: impl Future<Output = T>) -> T {
+        self.runtime.block_on(call)
+    }
+    /// List ranks for a domain, see `crate::Client::ranks`
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    pub fn ranks(&self, domain: &str) -> Result<RanksResponse, ApiError> {
+        self.block_on(self.inner.ranks(domain))
+    }
+    /// Get the metadata of a list by id, see `crate::Client::list`
+    ///
+    /// # Parameters
+    /// * `id` - id of the list
+    pub fn list(&self, id: &ListId) -> Result<ListsResponse, ApiError> {
+        self.block_on(self.inner.list(id)).map(ListsResponse::from)
+    }
+    /// Get the metadata of the daily list for a date, see `crate::Client::list_date`
+    ///
+    /// # Parameters
+    /// * `year`, `month`, `day` - date of the list
+    /// * `subdomains` - whether the list should include subdomains, or `None` for the API default
+    pub fn list_date(
+        &self,
+        year: u16,
+        month: u8,
+        day: u8,
+        subdomains: Option<bool>,
+    ) -> Result<ListsResponse, ApiError> {
+        self.block_on(self.inner.list_date(year, month, day, subdomains))
+            .map(ListsResponse::from)
+    }
+    /// Download a list, see `crate::Client::download_list`
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub fn download_list(&self, response: &ListsResponse) -> Result<Vec<RankedDomain>, Error> {
+        self.block_on(self.inner.download_list(response))
+    }
+}
@@ -0,0 +1,565 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::HttpTransport;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::cache::ListCache;
+use crate::circuit::CircuitBreaker;
+use crate::rate_limit::RateLimiter;
+use crate::redirect::DEFAULT_DOWNLOAD_HOSTS;
+use crate::{
+    API_BASE, Client, Credentials, DEFAULT_MAX_PAGES, DEFAULT_MAX_REDIRECTS, MetricsSink,
+    RequestHook, RetryPolicy, USER_AGENT,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::dns::{Name, Resolve, Resolving};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+#[cfg(not(target_arch = "wasm32"))]
+use std::net::SocketAddr;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Default timeout for metadata requests
+const DEFAULT_METADATA_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Builder for a `Client`
+///
+/// Tranco-level settings such as the base URL and retry policy are applied on top of the
+/// underlying `reqwest::Client`, which can be supplied with `transport` to share an existing
+/// connection pool. Otherwise, one is built from the HTTP settings (`connect_timeout`,
+/// `user_agent`, `proxy`, `default_headers`, and the connection pool, keep-alive, and HTTP/2
+/// settings).
+pub struct ClientBuilder {
+    transport: Option<reqwest::Client>,
+    #[cfg(not(target_arch = "wasm32"))]
+    connect_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_idle_timeout: Option<Option<Duration>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_max_idle_per_host: Option<usize>,
+    #[cfg(not(target_arch = "wasm32"))]
+    tcp_keepalive: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    http2_prior_knowledge: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    http2_keep_alive_interval: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    http2_keep_alive_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    http2_keep_alive_while_idle: bool,
+    user_agent: Option<HeaderValue>,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxies: Vec<reqwest::Proxy>,
+    #[cfg(not(target_arch = "wasm32"))]
+    dns_resolver: Option<Arc<dyn Resolve>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    dns_overrides: Vec<(String, Vec<SocketAddr>)>,
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    root_certificates: Vec<reqwest::Certificate>,
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    built_in_root_certs: bool,
+    default_headers: HeaderMap,
+    #[cfg(not(target_arch = "wasm32"))]
+    http_transport: Option<Arc<dyn HttpTransport>>,
+    base_url: String,
+    retry: RetryPolicy,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    metadata_timeout: Option<Duration>,
+    download_timeout: Option<Duration>,
+    request_hook: Option<RequestHook>,
+    #[cfg(not(target_arch = "wasm32"))]
+    cache: Option<ListCache>,
+    extra_params: Vec<(String, String)>,
+    max_pages: u32,
+    max_redirects: usize,
+    redirect_hosts: Option<Vec<String>>,
+    download_hosts: Option<Vec<String>>,
+    credentials: Option<Credentials>,
+    strict_dates: bool,
+    sort_ranks: bool,
+    max_concurrent_requests: Option<usize>,
+    rate_limit: Option<(u32, Duration)>,
+    max_download_rate: Option<u64>,
+    circuit_breaker: Option<(u32, Duration)>,
+}
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ClientBuilder {
+    /// Constructor
+    pub fn new() -> Self {
+        Self {
+            transport: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            connect_timeout: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_idle_timeout: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_max_idle_per_host: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            tcp_keepalive: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            http2_prior_knowledge: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            http2_keep_alive_interval: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            http2_keep_alive_timeout: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            http2_keep_alive_while_idle: false,
+            user_agent: Some(HeaderValue::from_static(USER_AGENT)),
+            #[cfg(not(target_arch = "wasm32"))]
+            proxies: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            dns_resolver: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            dns_overrides: Vec::new(),
+            #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+            root_certificates: Vec::new(),
+            #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+            built_in_root_certs: true,
+            default_headers: HeaderMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            http_transport: None,
+            base_url: API_BASE.into(),
+            retry: RetryPolicy::default(),
+            metrics: None,
+            metadata_timeout: Some(DEFAULT_METADATA_TIMEOUT),
+            download_timeout: None,
+            request_hook: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            cache: None,
+            extra_params: Vec::new(),
+            max_pages: DEFAULT_MAX_PAGES,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            redirect_hosts: None,
+            download_hosts: Some(
+                DEFAULT_DOWNLOAD_HOSTS
+                    .iter()
+                    .map(|host| host.to_string())
+                    .collect(),
+            ),
+            credentials: None,
+            strict_dates: false,
+            sort_ranks: false,
+            max_concurrent_requests: None,
+            rate_limit: None,
+            max_download_rate: None,
+            circuit_breaker: None,
+        }
+    }
+    /// Use an existing `reqwest::Client` to send requests
+    ///
+    /// The client is used as-is, so its connection pool, keep-alive, and DNS settings are shared
+    /// with the rest of the application. For `max_redirects` and `redirect_hosts` to fully apply,
+    /// the client should be built with `reqwest::redirect::Policy::none()`.
+    pub fn transport(mut self, client: reqwest::Client) -> Self {
+        self.transport = Some(client);
+        self
+    }
+    /// Timeout for establishing connections, defaults to no timeout
+    ///
+    /// Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+    /// How long idle connections are kept in the pool, defaults to reqwest's 90 seconds
+    ///
+    /// `None` keeps them until the server closes them, which suits jobs that fetch many lists
+    /// with pauses in between. Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+    /// Maximum number of idle connections kept per host, defaults to no limit
+    ///
+    /// Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+    /// Interval between TCP keep-alive probes, defaults to none
+    ///
+    /// Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+    /// Speak HTTP/2 without negotiating it first, for servers known to support it
+    ///
+    /// Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+    /// Send HTTP/2 pings at this interval to keep connections alive, defaults to never
+    ///
+    /// A connection is closed if a ping isn't acknowledged within `timeout`, if given, or
+    /// reqwest's default of 20 seconds. Pings are only sent while requests are in flight unless
+    /// `while_idle` is set. Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_keep_alive(
+        mut self,
+        interval: Duration,
+        timeout: Option<Duration>,
+        while_idle: bool,
+    ) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self.http2_keep_alive_timeout = timeout;
+        self.http2_keep_alive_while_idle = while_idle;
+        self
+    }
+    /// `User-Agent` header sent with every request, defaults to `USER_AGENT`
+    ///
+    /// Tranco asks researchers to identify themselves, e.g. with a project name and contact URL.
+    /// Ignored if a client is supplied with `transport`.
+    pub fn user_agent(mut self, user_agent: HeaderValue) -> Self {
+        self.user_agent = Some(user_agent);
+        self
+    }
+    /// Send requests through a proxy, in addition to any proxies added before
+    ///
+    /// Without a proxy, the system proxy settings are used. Ignored if a client is supplied with
+    /// `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+    /// Resolve hostnames with a custom resolver instead of the system's, e.g. one using DNS over
+    /// HTTPS
+    ///
+    /// Addresses given with `resolve` still take precedence. Ignored if a client is supplied
+    /// with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn dns_resolver<R: Resolve + 'static>(mut self, resolver: Arc<R>) -> Self {
+        self.dns_resolver = Some(resolver);
+        self
+    }
+    /// Connect to `addrs` for `domain` instead of resolving it, in addition to any domains
+    /// given before
+    ///
+    /// A port of 0 uses the default port of the URL's scheme. The download host needs an entry
+    /// too if it differs from the API host. Ignored if a client is supplied with `transport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn resolve(mut self, domain: impl Into<String>, addrs: Vec<SocketAddr>) -> Self {
+        self.dns_overrides.push((domain.into(), addrs));
+        self
+    }
+    /// Trust an additional root certificate, in addition to any added before
+    ///
+    /// Together with `tls_built_in_root_certs(false)`, this pins the client to the certificate
+    /// authority that issued the API's certificate (or to the certificate itself, if
+    /// self-signed), so an interception proxy with its own CA is rejected. List downloads may be
+    /// redirected to other hosts, whose issuers then need to be trusted as well. Ignored if a
+    /// client is supplied with `transport`.
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+    /// Whether to trust the TLS backend's built-in root certificates, defaults to true
+    ///
+    /// Ignored if a client is supplied with `transport`.
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    pub fn tls_built_in_root_certs(mut self, enabled: bool) -> Self {
+        self.built_in_root_certs = enabled;
+        self
+    }
+    /// Headers sent with every request
+    ///
+    /// Ignored if a client is supplied with `transport`; use `request_hook` to add headers to
+    /// requests sent through an existing client.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+    /// Header sent with every request, e.g. `From` with a contact address
+    ///
+    /// Adds to the headers set with `default_headers`, replacing any earlier value for the same
+    /// name. Ignored if a client is supplied with `transport`.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+    /// Send requests through a custom `HttpTransport` instead of a `reqwest::Client`
+    ///
+    /// Requests are still built with the client set by `transport` (or a default one), but
+    /// nothing is sent through it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http_transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.http_transport = Some(Arc::new(transport));
+        self
+    }
+    /// Base URL of the Tranco API, defaults to `API_BASE`
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+    /// Policy for retrying transient failures, defaults to never retrying
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+    /// Sink notified of every request, response, and downloaded body
+    pub fn metrics(mut self, metrics: impl MetricsSink + 'static) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+    /// Timeout for metadata requests (`ranks`, `list`, `list_date`), defaults to 30 seconds
+    ///
+    /// `None` disables the timeout. It can be overridden for a single call with `RequestOptions`.
+    pub fn metadata_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.metadata_timeout = timeout;
+        self
+    }
+    /// Timeout for list downloads, covering the whole transfer, defaults to no timeout
+    ///
+    /// Full lists can legitimately take minutes to download on slow links. It can be overridden
+    /// for a single call with `RequestOptions`.
+    pub fn download_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+    /// Function applied to every request before it is sent
+    ///
+    /// Useful for adding authentication headers or query parameters, e.g. when going through an
+    /// authenticating proxy or a private mirror. The hook also applies to list downloads, but the
+    /// credentials headers it adds are dropped when a download redirects to another origin.
+    pub fn request_hook(
+        mut self,
+        hook: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.request_hook = Some(Arc::new(hook));
+        self
+    }
+    /// Cache downloaded lists in a directory, see `Client::with_cache_dir`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>, max_age: Option<Duration>) -> Self {
+        self.cache = Some(ListCache::new(dir.into(), max_age));
+        self
+    }
+    /// Query parameters added to every `list`, `list_date`, and `list_date_query` request
+    ///
+    /// Allows using API parameters this crate doesn't support yet. Parameters are URL-encoded.
+    pub fn extra_params(mut self, params: Vec<(String, String)>) -> Self {
+        self.extra_params = params;
+        self
+    }
+    /// Maximum number of pages `ranks` follows before giving up, defaults to 100
+    pub fn max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+    /// Maximum number of redirects followed by a list download, defaults to 10
+    pub fn max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+    /// Hosts list downloads may be redirected to, defaults to allowing any host
+    ///
+    /// Hosts are compared case-insensitively. A redirect elsewhere fails the download with
+    /// `DownloadListError::UnexpectedRedirectHost`.
+    pub fn redirect_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.redirect_hosts = Some(hosts);
+        self
+    }
+    /// Hosts the download URLs returned by the API may point to, defaults to `tranco-list.eu`
+    ///
+    /// The host of the base URL is always allowed as well. Hosts are compared
+    /// case-insensitively, and a download URL elsewhere fails with
+    /// `DownloadListError::UnexpectedDownloadHost` before anything is requested from it, so a
+    /// spoofed API response can't point the client at an arbitrary server. `None` allows any
+    /// host. Redirects from an allowed host are checked with `redirect_hosts` instead.
+    pub fn download_hosts(mut self, hosts: Option<Vec<String>>) -> Self {
+        self.download_hosts = hosts;
+        self
+    }
+    /// Account used for authenticated endpoints, such as `Client::create_list`, defaults to none
+    ///
+    /// Credentials are only sent to endpoints that need them. Use `Credentials::from_env` to
+    /// read them from the environment.
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+    /// Whether `ranks` fails on a malformed date instead of returning it, defaults to `false`
+    ///
+    /// Dates are kept as the raw strings sent by the API, so by default a single malformed one
+    /// only surfaces when calling `DomainRank::parsed_date` on it. Strict mode fails the whole
+    /// response with `ApiError::MalformedDate` instead.
+    pub fn strict_dates(mut self, strict: bool) -> Self {
+        self.strict_dates = strict;
+        self
+    }
+    /// Whether `ranks` sorts its results by date, defaults to `false`
+    ///
+    /// The API doesn't guarantee any order, so by default ranks are returned as received.
+    pub fn sort_ranks(mut self, sort: bool) -> Self {
+        self.sort_ranks = sort;
+        self
+    }
+    /// Maximum number of requests in flight at once, defaults to no limit
+    ///
+    /// The limit is shared by every method and every clone of the built client. A request counts
+    /// until its response has been fully read or dropped, so list downloads hold their slot for
+    /// the whole transfer. On wasm32, requests only count until their headers arrive. A limit of
+    /// 0 is treated as 1.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max.max(1));
+        self
+    }
+    /// Limit the rate of requests to `requests` per `per`, defaults to no limit
+    ///
+    /// The limit is a token bucket shared by clones of the client, so it applies across all
+    /// endpoints and concurrent tasks. Up to `requests` requests may be sent in a burst, after
+    /// which they're spaced out evenly. Retries and redirects count as requests. A limit of 0 is
+    /// treated as 1.
+    pub fn rate_limit(mut self, requests: u32, per: Duration) -> Self {
+        self.rate_limit = Some((requests, per));
+        self
+    }
+    /// Limit the bandwidth of list downloads to `bytes_per_second`, defaults to no limit
+    ///
+    /// The limit is shared by all downloads of the client and its clones. Bodies are read no
+    /// faster than the limit allows, so the server is held back by TCP flow control. With the
+    /// `compression` feature, the limit applies to the decompressed bytes. A limit of 0 is
+    /// treated as 1.
+    pub fn max_download_rate(mut self, bytes_per_second: u64) -> Self {
+        self.max_download_rate = Some(bytes_per_second);
+        self
+    }
+    /// Fail fast after `failures` consecutive failures, defaults to never failing fast
+    ///
+    /// Failures are connection errors, timeouts, and 5xx responses, counted across clones of the
+    /// client. Once the limit is reached, requests fail with a `CircuitOpen` error without being
+    /// sent until `cool_down` has elapsed. The first request after that is sent as a trial, and
+    /// the others keep failing fast until it completes: a success closes the circuit, and a
+    /// failure opens it again. A limit of 0 is treated as 1.
+    pub fn circuit_breaker(mut self, failures: u32, cool_down: Duration) -> Self {
+        self.circuit_breaker = Some((failures, cool_down));
+        self
+    }
+    /// Build the client
+    ///
+    /// # Panics
+    /// Like `reqwest::Client::new`, panics if no client was supplied with `transport` and the
+    /// TLS backend can't be initialized
+    pub fn build(self) -> Client {
+        let client = self.transport.unwrap_or_else(|| {
+            let mut builder = reqwest::Client::builder().default_headers(self.default_headers);
+            if let Some(user_agent) = self.user_agent {
+                builder = builder.user_agent(user_agent);
+            }
+            // Browsers pick their own connection, proxy, DNS, and TLS settings, and follow
+            // redirects themselves
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                builder = builder.redirect(reqwest::redirect::Policy::none());
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.pool_idle_timeout {
+                    builder = builder.pool_idle_timeout(timeout);
+                }
+                if let Some(max) = self.pool_max_idle_per_host {
+                    builder = builder.pool_max_idle_per_host(max);
+                }
+                if let Some(interval) = self.tcp_keepalive {
+                    builder = builder.tcp_keepalive(interval);
+                }
+                if self.http2_prior_knowledge {
+                    builder = builder.http2_prior_knowledge();
+                }
+                if let Some(interval) = self.http2_keep_alive_interval {
+                    builder = builder
+                        .http2_keep_alive_interval(interval)
+                        .http2_keep_alive_while_idle(self.http2_keep_alive_while_idle);
+                    if let Some(timeout) = self.http2_keep_alive_timeout {
+                        builder = builder.http2_keep_alive_timeout(timeout);
+                    }
+                }
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                if let Some(resolver) = self.dns_resolver {
+                    builder = builder.dns_resolver(Arc::new(SharedResolver(resolver)));
+                }
+                for (domain, addrs) in &self.dns_overrides {
+                    builder = builder.resolve_to_addrs(domain, addrs);
+                }
+                #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+                {
+                    for certificate in self.root_certificates {
+                        builder = builder.add_root_certificate(certificate);
+                    }
+                    builder = builder.tls_built_in_root_certs(self.built_in_root_certs);
+                }
+            }
+            builder.build().expect("failed to build the HTTP client")
+        });
+        Client {
+            #[cfg(not(target_arch = "wasm32"))]
+            transport: self
+                .http_transport
+                .unwrap_or_else(|| Arc::new(client.clone())),
+            client,
+            base_url: self.base_url,
+            retry: self.retry,
+            metrics: self.metrics,
+            metadata_timeout: self.metadata_timeout,
+            download_timeout: self.download_timeout,
+            request_hook: self.request_hook,
+            #[cfg(not(target_arch = "wasm32"))]
+            cache: self.cache,
+            extra_params: self.extra_params,
+            max_pages: self.max_pages,
+            max_redirects: self.max_redirects,
+            redirect_hosts: self.redirect_hosts,
+            download_hosts: self.download_hosts,
+            credentials: self.credentials,
+            strict_dates: self.strict_dates,
+            sort_ranks: self.sort_ranks,
+            request_permits: self
+                .max_concurrent_requests
+                .map(|max| Arc::new(Semaphore::new(max))),
+            rate_limiter: self
+                .rate_limit
+                .map(|(requests, per)| Arc::new(RateLimiter::new(requests.into(), per))),
+            download_throttle: self.max_download_rate.map(|bytes_per_second| {
+                Arc::new(RateLimiter::new(bytes_per_second, Duration::from_secs(1)))
+            }),
+            circuit_breaker: self
+                .circuit_breaker
+                .map(|(failures, cool_down)| Arc::new(CircuitBreaker::new(failures, cool_down))),
+        }
+    }
+}
+
+/// Resolver given to `ClientBuilder::dns_resolver`, whose concrete type has been erased
+#[cfg(not(target_arch = "wasm32"))]
+struct SharedResolver(Arc<dyn Resolve>);
+#[cfg(not(target_arch = "wasm32"))]
+impl Resolve for SharedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        self.0.resolve(name)
+    }
+}
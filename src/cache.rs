@@ -0,0 +1,91 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use bytes::Bytes;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::fs;
+
+/// On-disk cache of downloaded list CSVs, keyed by list id and variant
+///
+/// Every distinct configuration, including whether subdomains are included, gets its own list
+/// id from Tranco. The same id is shared by the variants of a list (e.g. the top million and the
+/// full ranking), which are told apart by the final segment of their download URL.
+#[derive(Clone, Debug)]
+pub(crate) struct ListCache {
+    dir: PathBuf,
+    max_age: Option<Duration>,
+}
+impl ListCache {
+    pub(crate) fn new(dir: PathBuf, max_age: Option<Duration>) -> Self {
+        Self { dir, max_age }
+    }
+    /// Path of the cached CSV for a variant of a list
+    fn path(&self, list_id: &str, variant: &str) -> PathBuf {
+        let name: String = format!("{list_id}-{variant}")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{name}.csv"))
+    }
+    /// Read a cached list, if present and not older than `max_age`
+    pub(crate) async fn load(&self, list_id: &str, variant: &str) -> io::Result<Option<Bytes>> {
+        let path = self.path(list_id, variant);
+        let metadata = match fs::metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if let Some(max_age) = self.max_age {
+            // A modification time in the future counts as fresh
+            let age = metadata.modified()?.elapsed().unwrap_or_default();
+            if age > max_age {
+                return Ok(None);
+            }
+        }
+        fs::read(&path).await.map(Bytes::from).map(Some)
+    }
+    /// Write a list to the cache, replacing any previous copy
+    pub(crate) async fn store(
+        &self,
+        list_id: &str,
+        variant: &str,
+        csv_body: &[u8],
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.dir).await?;
+        let path = self.path(list_id, variant);
+        // Write to a temporary file first so readers never see a partial list
+        let partial = path.with_extension("csv.partial");
+        fs::write(&partial, csv_body).await?;
+        fs::rename(&partial, &path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_are_cached_separately() {
+        let cache = ListCache::new(PathBuf::from("cache"), None);
+        assert_eq!(
+            cache.path("X5KNN", "1000000"),
+            PathBuf::from("cache/X5KNN-1000000.csv")
+        );
+        assert_ne!(cache.path("X5KNN", "1000000"), cache.path("X5KNN", "full"));
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::time::Instant;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Circuit breaker shared by every request a client sends
+///
+/// After `threshold` consecutive failures the circuit opens and requests fail immediately until
+/// `cool_down` has elapsed. The circuit is then half-open: exactly one request is let through
+/// as a trial, and the others keep failing until it is recorded. A success closes the circuit,
+/// while a failure opens it again for another `cool_down`. A trial that is never recorded, e.g.
+/// because its future was dropped, is given up on after another `cool_down`, letting a new
+/// trial through.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cool_down: Duration,
+    state: Mutex<State>,
+}
+#[derive(Debug, Default)]
+struct State {
+    /// Failures since the last success
+    failures: u32,
+    /// When the circuit closes again, if it's open
+    open_until: Option<Instant>,
+}
+impl CircuitBreaker {
+    /// Open after `threshold` consecutive failures, treating 0 as 1
+    pub(crate) fn new(threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cool_down,
+            state: Mutex::new(State::default()),
+        }
+    }
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("circuit breaker lock poisoned")
+    }
+    /// Check whether a request may be sent, returning the remaining cool-down if not
+    ///
+    /// Once the cool-down has elapsed, only the first caller is let through, as the trial.
+    pub(crate) fn check(&self) -> Result<(), Duration> {
+        let mut state = self.state();
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        match open_until.checked_duration_since(now) {
+            Some(remaining) if !remaining.is_zero() => Err(remaining),
+            _ => {
+                // Hold the others back while the trial is in flight
+                state.open_until = Some(now + self.cool_down);
+                Ok(())
+            }
+        }
+    }
+    /// Record a request that reached a healthy upstream
+    pub(crate) fn record_success(&self) {
+        *self.state() = State::default();
+    }
+    /// Record a request that failed because of the upstream or the connection to it
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state();
+        state.failures = state.failures.saturating_add(1);
+        if state.failures >= self.threshold {
+            state.open_until = Some(Instant::now() + self.cool_down);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_until_threshold() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+    }
+
+    #[test]
+    fn half_open_lets_one_trial_through() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_err());
+        breaker.record_success();
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn failed_trial_reopens() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+    }
+}
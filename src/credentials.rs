@@ -0,0 +1,54 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::env;
+use std::fmt;
+
+/// Environment variable read by `Credentials::from_env` for the account's email address
+const EMAIL_VAR: &str = "TRANCO_EMAIL";
+/// Environment variable read by `Credentials::from_env` for the API token
+const TOKEN_VAR: &str = "TRANCO_API_TOKEN";
+
+/// Tranco account used for authenticated endpoints such as `Client::create_list`
+///
+/// Sent with HTTP basic authentication, using the email address of the account and an API token
+/// from its account page. The token is redacted from the `Debug` output.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Credentials {
+    pub email: String,
+    pub token: String,
+}
+impl Credentials {
+    /// Constructor
+    pub fn new(email: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            email: email.into(),
+            token: token.into(),
+        }
+    }
+    /// Read the credentials from `TRANCO_EMAIL` and `TRANCO_API_TOKEN`
+    ///
+    /// # Returns
+    /// `None` unless both variables are set to valid Unicode
+    pub fn from_env() -> Option<Self> {
+        Some(Self::new(
+            env::var(EMAIL_VAR).ok()?,
+            env::var(TOKEN_VAR).ok()?,
+        ))
+    }
+}
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("email", &self.email)
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
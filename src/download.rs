@@ -0,0 +1,66 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(target_arch = "wasm32")]
+use bytes::Bytes;
+#[cfg(target_arch = "wasm32")]
+use reqwest::{StatusCode, Url};
+
+/// Response of a list download, whose body is read with `Client::next_chunk`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type Download = reqwest::Response;
+
+/// Response of a list download, whose body is read with `Client::next_chunk`
+///
+/// The fetch backend of browsers only hands out the body whole, so it is read as a single chunk.
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Download {
+    url: Url,
+    status: StatusCode,
+    content_length: Option<u64>,
+    body: Option<Box<reqwest::Response>>,
+}
+#[cfg(target_arch = "wasm32")]
+impl Download {
+    /// URL the response came from
+    pub(crate) fn url(&self) -> &Url {
+        &self.url
+    }
+    /// Status of the response
+    pub(crate) fn status(&self) -> StatusCode {
+        self.status
+    }
+    /// Advertised length of the body, if any
+    pub(crate) fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+    /// Read the whole body on the first call, and nothing after that
+    pub(crate) async fn chunk(&mut self) -> reqwest::Result<Option<Bytes>> {
+        match self.body.take() {
+            Some(response) => response.bytes().await.map(Some),
+            None => Ok(None),
+        }
+    }
+    /// Read the whole body
+    pub(crate) async fn bytes(mut self) -> reqwest::Result<Bytes> {
+        Ok(self.chunk().await?.unwrap_or_default())
+    }
+}
+#[cfg(target_arch = "wasm32")]
+impl From<reqwest::Response> for Download {
+    fn from(response: reqwest::Response) -> Self {
+        Self {
+            url: response.url().clone(),
+            status: response.status(),
+            content_length: response.content_length(),
+            body: Some(Box::new(response)),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Client, Error, ListsResponse, RankedDomain, parse_line};
+
+/// Downloader that reuses its buffers across many list downloads
+///
+/// Meant for backfills that download one list after another: the buffer holding partial lines
+/// keeps its allocation between downloads, and the output vector is reserved up front to the
+/// size of the largest list seen so far.
+pub struct ListDownloader {
+    client: Client,
+    line_buffer: Vec<u8>,
+    capacity_hint: usize,
+}
+impl ListDownloader {
+    /// Constructor
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            line_buffer: Vec::new(),
+            capacity_hint: 0,
+        }
+    }
+    /// Download a list into `out`, replacing its contents
+    ///
+    /// Reusing the same `out` across calls avoids reallocating it for each list.
+    ///
+    /// # Parameters
+    /// * `response` - ListsResponse from either `list` or `list_date`
+    /// * `out` - vector the parsed list is written to
+    pub async fn download_into(
+        &mut self,
+        response: &ListsResponse,
+        out: &mut Vec<RankedDomain>,
+    ) -> Result<(), Error> {
+        out.clear();
+        out.reserve(self.capacity_hint);
+        self.client
+            .for_each_chunk_line_in(
+                &mut self.line_buffer,
+                response,
+                |_| (),
+                |line| {
+                    out.push(parse_line(line)?);
+                    Ok(())
+                },
+            )
+            .await?;
+        self.capacity_hint = self.capacity_hint.max(out.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Client, DownloadListError, Error, ListsResponse, RankedDomain, parse_list};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use std::borrow::Cow;
+use std::io;
+
+/// How `Client::download_list_decoded` turns the bytes of a list into text
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DecodePolicy {
+    /// Decode as UTF-8, failing on invalid sequences like `download_list` does
+    #[default]
+    Strict,
+    /// Decode as UTF-8, replacing invalid sequences with U+FFFD
+    Lossy,
+    /// Detect the encoding: a byte order mark wins, then UTF-8 if the whole list is valid UTF-8,
+    /// and otherwise Windows-1252, a superset of Latin-1 that decodes any byte
+    ///
+    /// Invalid sequences in an encoding announced by a byte order mark are replaced with U+FFFD.
+    Detect,
+}
+
+impl Client {
+    /// Download a list, decoding it according to a policy instead of assuming UTF-8
+    ///
+    /// Tranco serves UTF-8, but mirrors may not.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `policy` - how to decode the list
+    ///
+    /// # Returns
+    /// The list, and the encoding it was decoded with
+    pub async fn download_list_decoded(
+        &self,
+        response: &ListsResponse,
+        policy: DecodePolicy,
+    ) -> Result<(Vec<RankedDomain>, &'static Encoding), Error> {
+        let csv_body = self.download_bytes(response).await?;
+        let (text, encoding) = decode(&csv_body, policy)?;
+        Ok((parse_list(text.as_bytes())?, encoding))
+    }
+}
+
+/// Decode the bytes of a list according to a policy
+fn decode(
+    bytes: &[u8],
+    policy: DecodePolicy,
+) -> Result<(Cow<'_, str>, &'static Encoding), DownloadListError> {
+    match policy {
+        DecodePolicy::Strict => {
+            let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+            let text = std::str::from_utf8(bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            Ok((Cow::Borrowed(text), UTF_8))
+        }
+        DecodePolicy::Lossy => Ok((UTF_8.decode_with_bom_removal(bytes).0, UTF_8)),
+        DecodePolicy::Detect => {
+            if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+                let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+                return Ok((text, encoding));
+            }
+            Ok(match std::str::from_utf8(bytes) {
+                Ok(text) => (Cow::Borrowed(text), UTF_8),
+                Err(_) => (
+                    WINDOWS_1252.decode_without_bom_handling(bytes).0,
+                    WINDOWS_1252,
+                ),
+            })
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{ApiError, Client, DOWNLOAD_CONCURRENCY, ListDateQuery, ListsResponse, days};
+use chrono::{Months, NaiveDate, Utc};
+use futures_util::{StreamExt, TryStreamExt, stream};
+use reqwest::StatusCode;
+use std::collections::BTreeMap;
+
+/// Number of days `latest_list` walks back before giving up
+const MAX_LOOKBACK_DAYS: usize = 7;
+
+/// Outcome of `Client::latest_list`: the date chosen and every date skipped on the way there
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resolution {
+    /// Date of the list that was returned
+    pub chosen: NaiveDate,
+    /// Dates tried before `chosen`, most recent first, and why each was skipped
+    pub skipped: Vec<(NaiveDate, SkipReason)>,
+}
+
+/// Why a date has no usable list, see `Client::latest_list` and `Client::lists_between`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+    /// The API has no list for the date (404)
+    NotFound,
+    /// The list exists but hasn't been generated yet
+    NotAvailable,
+    /// Generation of the list failed
+    Failed,
+}
+
+/// Outcome of fetching the metadata of one day's list, see `Client::lists_between`
+#[derive(Debug)]
+pub enum DailyList {
+    /// The list is available
+    Available(Box<ListsResponse>),
+    /// The day has no usable list
+    Missing(SkipReason),
+    /// Fetching the metadata failed, e.g. with a network error
+    Error(ApiError),
+}
+
+impl Client {
+    /// Find the most recent available daily list
+    ///
+    /// Starts from today (UTC) and walks back one day at a time, for at most `MAX_LOOKBACK_DAYS`
+    /// days, until a list that is available and didn't fail is found.
+    ///
+    /// # Parameters
+    /// * `subdomains` - whether the list should include subdomains, or `None` for the API default
+    ///
+    /// # Returns
+    /// The list and a `Resolution` recording which dates were skipped and why. If no date within
+    /// the lookback has a list, `ApiError::NoListFound` carries the same record.
+    pub async fn latest_list(
+        &self,
+        subdomains: Option<bool>,
+    ) -> Result<(ListsResponse, Resolution), ApiError> {
+        let today = Utc::now().date_naive();
+        let mut skipped = Vec::new();
+        for date in today.iter_days().rev().take(MAX_LOOKBACK_DAYS) {
+            let query = ListDateQuery::new(date).maybe_subdomains(subdomains);
+            match usable_list(self.list_date_query(&query).await)? {
+                Ok(list) => {
+                    let resolution = Resolution {
+                        chosen: date,
+                        skipped,
+                    };
+                    return Ok((list, resolution));
+                }
+                Err(reason) => skipped.push((date, reason)),
+            }
+        }
+        Err(ApiError::NoListFound(skipped))
+    }
+    /// Find the most recent available daily list and its date
+    ///
+    /// Like `latest_list`, for when the dates that were skipped don't matter.
+    ///
+    /// # Parameters
+    /// * `subdomains` - whether the list should include subdomains, or `None` for the API default
+    pub async fn latest(
+        &self,
+        subdomains: Option<bool>,
+    ) -> Result<(ListsResponse, NaiveDate), ApiError> {
+        let (list, resolution) = self.latest_list(subdomains).await?;
+        Ok((list, resolution.chosen))
+    }
+    /// Get every available daily list of a month
+    ///
+    /// The metadata of up to `DOWNLOAD_CONCURRENCY` days is fetched at a time. Days without a
+    /// list, or whose list isn't available or failed, are left out.
+    ///
+    /// # Parameters
+    /// * `year`, `month` - month to list
+    /// * `subdomains` - whether the lists should include subdomains, or `None` for the API default
+    ///
+    /// # Returns
+    /// The lists in date order, or `ApiError::InvalidMonth` if `month` isn't between 1 and 12
+    pub async fn lists_in_month(
+        &self,
+        year: u16,
+        month: u8,
+        subdomains: Option<bool>,
+    ) -> Result<Vec<(NaiveDate, ListsResponse)>, ApiError> {
+        let Some(start) = NaiveDate::from_ymd_opt(year.into(), month.into(), 1) else {
+            return Err(ApiError::InvalidMonth { year, month });
+        };
+        let end = start
+            .checked_add_months(Months::new(1))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(NaiveDate::MAX);
+        let lists: Vec<_> = stream::iter(days(start, end))
+            .map(|date| async move {
+                let query = ListDateQuery::new(date).maybe_subdomains(subdomains);
+                let list = usable_list(self.list_date_query(&query).await)?;
+                Ok::<_, ApiError>(list.ok().map(|list| (date, list)))
+            })
+            .buffered(DOWNLOAD_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(lists.into_iter().flatten().collect())
+    }
+    /// Get the metadata of every daily list in a date range
+    ///
+    /// Up to `concurrency` days are fetched at a time (at least one). An error on one day
+    /// doesn't stop the others, so the result tells days without a usable list apart from days
+    /// that couldn't be checked.
+    ///
+    /// # Parameters
+    /// * `start` - first date of the range (inclusive)
+    /// * `end` - last date of the range (inclusive)
+    /// * `subdomains` - whether the lists should include subdomains, or `None` for the API default
+    /// * `concurrency` - maximum number of requests in flight
+    ///
+    /// # Returns
+    /// One entry per date in the range, or nothing if `end` is before `start`
+    pub async fn lists_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        subdomains: Option<bool>,
+        concurrency: usize,
+    ) -> BTreeMap<NaiveDate, DailyList> {
+        stream::iter(days(start, end))
+            .map(|date| async move {
+                let query = ListDateQuery::new(date).maybe_subdomains(subdomains);
+                let list = match usable_list(self.list_date_query(&query).await) {
+                    Ok(Ok(list)) => DailyList::Available(Box::new(list)),
+                    Ok(Err(reason)) => DailyList::Missing(reason),
+                    Err(e) => DailyList::Error(e),
+                };
+                (date, list)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+/// Sort the outcome of fetching a daily list's metadata into a usable list, a reason to skip
+/// the date, or an error
+fn usable_list(
+    result: Result<ListsResponse, ApiError>,
+) -> Result<Result<ListsResponse, SkipReason>, ApiError> {
+    match result {
+        Ok(list) if list.failed => Ok(Err(SkipReason::Failed)),
+        Ok(list) if !list.available => Ok(Err(SkipReason::NotAvailable)),
+        Ok(list) => Ok(Ok(list)),
+        Err(ApiError::Request(e)) if e.status() == Some(StatusCode::NOT_FOUND) => {
+            Ok(Err(SkipReason::NotFound))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_in_month_rejects_invalid_month() {
+        let client = Client::new();
+        for month in [0, 13] {
+            assert!(matches!(
+                client.lists_in_month(2025, month, None).await,
+                Err(ApiError::InvalidMonth { year: 2025, month: m }) if m == month
+            ));
+        }
+    }
+}
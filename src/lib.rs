@@ -8,135 +8,1732 @@
 //
 // You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use serde::Deserialize;
+#[cfg(feature = "reqwest")]
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+#[cfg(feature = "reqwest")]
+use futures_util::{Stream, StreamExt, TryStreamExt, future, stream};
+#[cfg(feature = "reqwest")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "reqwest")]
+use sha2::{Digest, Sha256};
+#[cfg(any(feature = "reqwest", feature = "psl"))]
+use std::borrow::Cow;
+use std::cmp::Ordering;
+#[cfg(feature = "reqwest")]
+use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::io::{self, BufRead, BufReader, Cursor};
+use std::io::{self, BufRead, Write};
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+use std::path::PathBuf;
+#[cfg(feature = "reqwest")]
+use std::sync::Arc;
+#[cfg(feature = "reqwest")]
+use std::time::Duration;
+#[cfg(feature = "reqwest")]
+use time::Instant;
+#[cfg(feature = "reqwest")]
+use tokio::sync::Semaphore;
+#[cfg(feature = "reqwest")]
+pub use tokio_util::sync::CancellationToken;
 
-const API_BASE: &str = "https://tranco-list.eu/api";
+#[cfg(feature = "reqwest")]
+mod aggregate;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "reqwest")]
+mod builder;
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+mod cache;
+#[cfg(feature = "reqwest")]
+mod circuit;
+mod credentials;
+#[cfg(feature = "reqwest")]
+mod download;
+#[cfg(feature = "reqwest")]
+mod downloader;
+#[cfg(feature = "encoding")]
+mod encoding;
+#[cfg(feature = "reqwest")]
+mod latest;
+#[cfg(any(feature = "reqwest", feature = "minimal"))]
+mod lines;
+#[cfg(feature = "reqwest")]
+mod list;
+mod list_id;
+#[cfg(feature = "reqwest")]
+mod metrics;
+#[cfg(feature = "minimal")]
+pub mod minimal;
+#[cfg(feature = "reqwest")]
+mod options;
+#[cfg(feature = "psl")]
+mod pld;
+mod query;
+mod ranked_list;
+#[cfg(feature = "reqwest")]
+mod rate_limit;
+#[cfg(feature = "__client")]
+mod redirect;
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+mod resume;
+#[cfg(feature = "reqwest")]
+mod retry;
+#[cfg(feature = "tower")]
+mod service;
+#[cfg(feature = "ureq")]
+pub mod sync;
+#[cfg(feature = "reqwest")]
+mod time;
+#[cfg(feature = "reqwest")]
+mod timed;
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+mod transport;
+#[cfg(feature = "reqwest")]
+mod wait;
+
+#[cfg(feature = "reqwest")]
+pub use builder::ClientBuilder;
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+use cache::ListCache;
+#[cfg(feature = "reqwest")]
+use circuit::CircuitBreaker;
+pub use credentials::Credentials;
+#[cfg(feature = "reqwest")]
+use download::Download;
+#[cfg(feature = "reqwest")]
+pub use downloader::ListDownloader;
+#[cfg(feature = "encoding")]
+pub use encoding::DecodePolicy;
+#[cfg(feature = "encoding")]
+pub use encoding_rs::Encoding;
+#[cfg(feature = "reqwest")]
+pub use latest::{DailyList, Resolution, SkipReason};
+#[cfg(feature = "reqwest")]
+pub use list::List;
+pub use list_id::{ListId, ListIdError};
+#[cfg(feature = "reqwest")]
+pub use metrics::{Endpoint, MetricsSink};
+#[cfg(feature = "reqwest")]
+pub use options::RequestOptions;
+#[cfg(feature = "psl")]
+pub use pld::pay_level_domain;
+pub use query::{ListDateQuery, ListVariant};
+pub use ranked_list::{ListStats, RankedList};
+#[cfg(feature = "reqwest")]
+use rate_limit::RateLimiter;
+#[cfg(feature = "reqwest")]
+pub use redirect::{DownloadProvenance, RedirectHop};
+#[cfg(feature = "reqwest")]
+pub use retry::RetryPolicy;
+#[cfg(feature = "tower")]
+pub use service::{TrancoRequest, TrancoResponse};
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+pub use transport::{HttpTransport, RequestTimeout, TransportBody, TransportError};
+
+/// Default base URL of the Tranco API
+pub const API_BASE: &str = "https://tranco-list.eu/api";
+/// `User-Agent` sent by default, identifying the library and its version
+pub const USER_AGENT: &str = concat!("tranco-rs/", env!("CARGO_PKG_VERSION"));
+/// Maximum number of characters of an unexpected response body kept for error messages
+#[cfg(feature = "reqwest")]
+const BODY_SNIPPET_LEN: usize = 200;
+/// Maximum number of lists downloaded at once by the multi-date helpers
+#[cfg(feature = "reqwest")]
+const DOWNLOAD_CONCURRENCY: usize = 4;
+
+/// Default maximum number of pages followed by `Client::ranks`
+#[cfg(feature = "__client")]
+const DEFAULT_MAX_PAGES: u32 = 100;
+/// Default maximum number of redirects followed by a list download
+#[cfg(feature = "__client")]
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Function applied to every request before it is sent, see `ClientBuilder::request_hook`
+#[cfg(feature = "reqwest")]
+pub type RequestHook =
+    Arc<dyn Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Send + Sync>;
 
 /// Client used to make Tranco API calls
+///
+/// Cloning is cheap, and clones share the underlying connection pool and metrics sink. The client
+/// is `Send + Sync`, so it can also be shared across tasks behind an `Arc`.
+#[cfg(feature = "reqwest")]
+#[derive(Clone)]
 pub struct Client {
     client: reqwest::Client,
+    #[cfg(not(target_arch = "wasm32"))]
+    transport: Arc<dyn HttpTransport>,
+    base_url: String,
+    retry: RetryPolicy,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    metadata_timeout: Option<Duration>,
+    download_timeout: Option<Duration>,
+    request_hook: Option<RequestHook>,
+    #[cfg(not(target_arch = "wasm32"))]
+    cache: Option<ListCache>,
+    extra_params: Vec<(String, String)>,
+    max_pages: u32,
+    max_redirects: usize,
+    redirect_hosts: Option<Vec<String>>,
+    download_hosts: Option<Vec<String>>,
+    credentials: Option<Credentials>,
+    strict_dates: bool,
+    sort_ranks: bool,
+    request_permits: Option<Arc<Semaphore>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    download_throttle: Option<Arc<RateLimiter>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+}
+// Sharing a client across tasks must keep working as fields are added
+#[cfg(feature = "reqwest")]
+const _: () = {
+    const fn assert_shareable<T: Send + Sync + Clone>() {}
+    assert_shareable::<Client>();
+};
+#[cfg(feature = "reqwest")]
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Client> for Client {
+    fn from(client: reqwest::Client) -> Self {
+        Self::from_client(client)
+    }
+}
+#[cfg(feature = "reqwest")]
+impl Client {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+    /// Constructor from client
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self::builder().transport(client).build()
+    }
+    /// Constructor from a `reqwest-middleware` client, so every request goes through its
+    /// middleware stack
+    #[cfg(all(feature = "middleware", not(target_arch = "wasm32")))]
+    pub fn from_middleware(client: reqwest_middleware::ClientWithMiddleware) -> Self {
+        Self::builder().http_transport(client).build()
+    }
+    /// Report the size of a response body to the metrics sink
+    fn record_bytes(&self, endpoint: Endpoint, bytes: u64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.on_bytes(endpoint, bytes);
+        }
+    }
+    /// Cache downloaded lists in a directory
+    ///
+    /// `download_list` and its variants serve a list from the cache if it was stored less than
+    /// `max_age` ago (or at any time if `max_age` is `None`), and store every list they download
+    /// in full. Streaming downloads read from the cache but don't write to it.
+    ///
+    /// # Parameters
+    /// * `dir` - directory holding one CSV per list id and variant, created if needed
+    /// * `max_age` - age after which a cached list is downloaded again
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>, max_age: Option<Duration>) -> Self {
+        self.cache = Some(ListCache::new(dir.into(), max_age));
+        self
+    }
+    /// Point the client at a different API base URL, e.g. a mirror, a caching proxy, or a mock
+    /// server, see `ClientBuilder::base_url`
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+    /// Create a builder to configure a client
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+    /// Attach the client's credentials to a request for an authenticated endpoint
+    fn authenticate(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::RequestBuilder, ApiError> {
+        let credentials = self
+            .credentials
+            .as_ref()
+            .ok_or(ApiError::MissingCredentials)?;
+        Ok(request.basic_auth(&credentials.email, Some(&credentials.token)))
+    }
+    /// Send a request, retrying transient failures according to the retry policy
+    async fn send(
+        &self,
+        endpoint: Endpoint,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, SendError> {
+        self.send_with_origin(endpoint, request, false).await
+    }
+    /// Send a request, see `send`
+    ///
+    /// If `cross_origin` is set, the request is going to another origin than the one the caller
+    /// asked for (e.g. a redirect to a CDN), so the headers carrying credentials are removed after
+    /// the request hook has run.
+    async fn send_with_origin(
+        &self,
+        endpoint: Endpoint,
+        request: reqwest::RequestBuilder,
+        cross_origin: bool,
+    ) -> Result<reqwest::Response, SendError> {
+        let request = match &self.request_hook {
+            Some(hook) => hook(request),
+            None => request,
+        };
+        // Timeouts set on the request itself, e.g. from `RequestOptions`, take precedence
+        let timeout = match endpoint {
+            Endpoint::Download => self.download_timeout,
+            _ => self.metadata_timeout,
+        };
+        let mut attempt = 0;
+        loop {
+            if let Some(breaker) = &self.circuit_breaker {
+                breaker.check().map_err(SendError::CircuitOpen)?;
+            }
+            let mut retryable = false;
+            let permit = match &self.request_permits {
+                Some(permits) => Some(Arc::new(
+                    Arc::clone(permits)
+                        .acquire_owned()
+                        .await
+                        .expect("request semaphore is never closed"),
+                )),
+                None => None,
+            };
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire().await;
+            }
+            if let Some(metrics) = &self.metrics {
+                metrics.on_request(endpoint);
+            }
+            let start = Instant::now();
+            let result = match request
+                .try_clone()
+                .expect("requests have no streaming body")
+                .build()
+            {
+                Ok(mut request) => {
+                    if request.timeout().is_none() {
+                        *request.timeout_mut() = timeout;
+                    }
+                    if cross_origin {
+                        redirect::strip_credentials(request.headers_mut());
+                    }
+                    retryable = matches!(
+                        *request.method(),
+                        reqwest::Method::GET | reqwest::Method::HEAD
+                    );
+                    self.execute(request).await
+                }
+                Err(e) => Err(SendError::Request(e)),
+            };
+            // Keep the permit until the body has been read, by tying it to the response
+            #[cfg(not(target_arch = "wasm32"))]
+            let result = result.map(|mut response| {
+                if let Some(permit) = permit {
+                    response.extensions_mut().insert(permit);
+                }
+                response
+            });
+            // Browser responses have no extensions, so there it only lasts until the headers arrive
+            #[cfg(target_arch = "wasm32")]
+            drop(permit);
+            if let Some(metrics) = &self.metrics {
+                let status = result.as_ref().ok().map(reqwest::Response::status);
+                metrics.on_response(endpoint, status, start.elapsed());
+            }
+            let result = result.and_then(|response| {
+                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(SendError::RateLimited(retry::retry_after(
+                        response.headers(),
+                    )));
+                }
+                Ok(response.error_for_status()?)
+            });
+            if let Some(breaker) = &self.circuit_breaker {
+                match &result {
+                    Err(SendError::Request(e)) if retry::is_transient(e) => {
+                        breaker.record_failure()
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    Err(SendError::Transport(_)) => breaker.record_failure(),
+                    _ => breaker.record_success(),
+                }
+            }
+            match result {
+                Err(SendError::Request(e))
+                    if retryable && attempt < self.retry.max_retries && retry::is_transient(&e) =>
+                {
+                    time::sleep(self.retry.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(SendError::RateLimited(retry_after))
+                    if retryable
+                        && self.retry.honor_retry_after
+                        && attempt < self.retry.max_retries
+                        && retry_after.is_none_or(|delay| delay <= self.retry.max_backoff) =>
+                {
+                    let delay = retry_after.unwrap_or_else(|| self.retry.backoff(attempt));
+                    time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+    /// Send a built request with the browser's fetch API, in place of an `HttpTransport`
+    #[cfg(target_arch = "wasm32")]
+    async fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response, SendError> {
+        Ok(self.client.execute(request).await?)
+    }
+    /// List ranks for a domain
+    ///
+    /// If the API splits the ranks over several pages linked by a `next` URL, every page is
+    /// fetched and concatenated, failing with `ApiError::TooManyPages` after `max_pages` pages.
+    ///
+    /// Ranks come in the order the API sends them, which isn't guaranteed to be by date, unless
+    /// the client was built with `ClientBuilder::sort_ranks`.
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    pub async fn ranks(&self, domain: &str) -> Result<RanksResponse, ApiError> {
+        self.ranks_with_options(domain, &RequestOptions::default())
+            .await
+    }
+    /// List ranks for a domain, overriding the client's configuration for this call, see `ranks`
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    /// * `options` - options for the requests of this call
+    pub async fn ranks_with_options(
+        &self,
+        domain: &str,
+        options: &RequestOptions,
+    ) -> Result<RanksResponse, ApiError> {
+        let mut url = reqwest::Url::parse(&format!("{}/ranks/domain/{domain}", self.base_url))
+            .map_err(ApiError::InvalidUrl)?;
+        let mut ranks = Vec::new();
+        for _ in 0..self.max_pages {
+            let page: RanksPage = decode_json(
+                self.send(Endpoint::Ranks, options.apply(self.client.get(url.clone())))
+                    .await?,
+            )
+            .await?;
+            if self.strict_dates {
+                for rank in &page.ranks {
+                    rank.parsed_date()
+                        .map_err(|source| ApiError::MalformedDate {
+                            date: rank.date.clone(),
+                            source,
+                        })?;
+                }
+            }
+            ranks.extend(page.ranks);
+            match page.next {
+                Some(next) => url = url.join(&next).map_err(ApiError::InvalidUrl)?,
+                None => {
+                    let mut ranks = RanksResponse { ranks };
+                    if self.sort_ranks {
+                        ranks.sort_by_date();
+                    }
+                    return Ok(ranks);
+                }
+            }
+        }
+        Err(ApiError::TooManyPages(self.max_pages))
+    }
+    /// List ranks for a domain, mapping an empty response to `None`
+    ///
+    /// `None` means the domain wasn't in any daily list of the past 30 days, either because it
+    /// isn't tracked or because it dropped out, see `RanksResponse::is_empty`.
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    pub async fn ranks_opt(&self, domain: &str) -> Result<Option<RanksResponse>, ApiError> {
+        let ranks = self.ranks(domain).await?;
+        Ok((!ranks.is_empty()).then_some(ranks))
+    }
+    /// List ranks for many domains, yielding each response as soon as it completes
+    ///
+    /// Domains are only taken from `domains` as requests complete, so memory use stays flat
+    /// however many there are. Items arrive in completion order, each tagged with its domain,
+    /// and a failure for one domain doesn't end the stream.
+    ///
+    /// # Parameters
+    /// * `domains` - domains to query ranks for
+    /// * `concurrency` - maximum number of requests in flight, at least 1
+    pub fn ranks_stream<'a, I>(
+        &'a self,
+        domains: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = (String, Result<RanksResponse, ApiError>)> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+        I::Item: Into<String>,
+    {
+        stream::iter(domains)
+            .map(move |domain| async move {
+                let domain = domain.into();
+                let ranks = self.ranks(&domain).await;
+                (domain, ranks)
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+    /// Get the most recent rank of a domain
+    ///
+    /// # Parameters
+    /// * `domain` - domain to look up
+    ///
+    /// # Returns
+    /// The entry with the latest date from `ranks`, or `ApiError::NoRanks` if the domain has none
+    pub async fn poll_rank(&self, domain: &str) -> Result<DomainRank, ApiError> {
+        self.ranks(domain)
+            .await?
+            .ranks
+            .into_iter()
+            .max_by(|a, b| a.date.cmp(&b.date))
+            .ok_or_else(|| ApiError::NoRanks(domain.into()))
+    }
+    /// Get how a domain's rank changed between the two most recent daily lists it was in
+    ///
+    /// Useful for alerting on large swings. Entries whose date can't be parsed are ignored.
+    ///
+    /// # Parameters
+    /// * `domain` - domain to get the rank change of
+    ///
+    /// # Returns
+    /// `None` if the domain has fewer than two ranks
+    pub async fn latest_rank_change(&self, domain: &str) -> Result<Option<RankDelta>, ApiError> {
+        let by_date = self.ranks(domain).await?.into_by_date();
+        let mut latest = by_date.into_iter().rev();
+        let (Some((date, current)), Some((_, previous))) = (latest.next(), latest.next()) else {
+            return Ok(None);
+        };
+        Ok(Some(RankDelta {
+            date,
+            previous,
+            current,
+            change: current as i64 - previous as i64,
+        }))
+    }
+    /// Get the metadata of a list by id
+    ///
+    /// Fails with `ApiError::MismatchedListId` if the API (or a cache or proxy in between)
+    /// answers with the metadata of a different list.
+    ///
+    /// # Parameters
+    /// * `id` - id of the list
+    ///
+    /// # Returns
+    /// A handle bundling the metadata with this client, to download the list later
+    pub async fn list(&self, id: &ListId) -> Result<List, ApiError> {
+        self.list_with_options(id, &RequestOptions::default()).await
+    }
+    /// Get the metadata of a list by id, overriding the client's configuration for this call, see
+    /// `list`
+    ///
+    /// # Parameters
+    /// * `id` - id of the list
+    /// * `options` - options for the request of this call
+    pub async fn list_with_options(
+        &self,
+        id: &ListId,
+        options: &RequestOptions,
+    ) -> Result<List, ApiError> {
+        let url = format!("{}/lists/id/{id}", self.base_url);
+        let request = options.apply(self.client.get(url).query(&self.extra_params));
+        let response: ListsResponse =
+            decode_json(self.send(Endpoint::List, request).await?).await?;
+        if response.list_id != id.as_ref() {
+            return Err(ApiError::MismatchedListId {
+                requested: id.clone(),
+                returned: response.list_id,
+            });
+        }
+        Ok(List::new(self.clone(), response))
+    }
+    /// Get only the configuration of a list by id, without downloading it
+    ///
+    /// # Parameters
+    /// * `id` - id of the list
+    pub async fn list_configuration(&self, id: &ListId) -> Result<Configuration, ApiError> {
+        Ok(self.list(id).await?.into_metadata().configuration)
+    }
+    /// List ranks for a domain
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    pub async fn list_date(
+        &self,
+        year: u16,
+        month: u8,
+        day: u8,
+        subdomains: Option<bool>,
+    ) -> Result<List, ApiError> {
+        self.list_date_with_options(year, month, day, subdomains, &RequestOptions::default())
+            .await
+    }
+    /// Get the metadata of the daily list of a date, overriding the client's configuration for
+    /// this call, see `list_date`
+    ///
+    /// # Parameters
+    /// * `year`, `month`, `day` - date of the daily list
+    /// * `subdomains` - whether the list should include subdomains, or `None` for the API default
+    /// * `options` - options for the request of this call
+    pub async fn list_date_with_options(
+        &self,
+        year: u16,
+        month: u8,
+        day: u8,
+        subdomains: Option<bool>,
+        options: &RequestOptions,
+    ) -> Result<List, ApiError> {
+        let url = format!(
+            "{}/lists/date/{year:04}{month:02}{day:02}{}",
+            self.base_url,
+            if let Some(subdomains) = subdomains {
+                format!("?subdomains={subdomains}")
+            } else {
+                String::new()
+            }
+        );
+        let request = options.apply(self.client.get(url).query(&self.extra_params));
+        let response = decode_json(self.send(Endpoint::ListDate, request).await?).await?;
+        check_subdomains(subdomains, &response)?;
+        Ok(List::new(self.clone(), response))
+    }
+    /// Get the metadata of the daily list matching a query
+    ///
+    /// If the query sets `subdomains`, fails with `ApiError::SubdomainFlagMismatch` when the
+    /// returned list wasn't generated accordingly. `list_date` performs the same check.
+    ///
+    /// # Parameters
+    /// * `query` - date of the list and optional parameters
+    pub async fn list_date_query(&self, query: &ListDateQuery) -> Result<ListsResponse, ApiError> {
+        let url = format!(
+            "{}/lists/date/{}",
+            self.base_url,
+            query.date().format("%Y%m%d")
+        );
+        let request = self
+            .client
+            .get(url)
+            .query(query)
+            .query(query.extra_params())
+            .query(&self.extra_params);
+        let response: ListsResponse =
+            decode_json(self.send(Endpoint::ListDate, request).await?).await?;
+        check_subdomains(query.subdomains_flag(), &response)?;
+        // The API already links to the top million
+        Ok(match query.list_variant() {
+            ListVariant::Top => response,
+            variant => response.with_variant(variant),
+        })
+    }
+    /// Generate a custom list from a configuration
+    ///
+    /// The configuration is checked with `Configuration::validate` before anything is sent, and
+    /// the request is authenticated with the client's credentials, failing with
+    /// `ApiError::MissingCredentials` if there are none. Lists take a while to
+    /// generate, so the returned metadata usually isn't `available` yet; poll it with `list`.
+    ///
+    /// # Parameters
+    /// * `configuration` - configuration of the list to generate
+    pub async fn create_list(
+        &self,
+        configuration: &Configuration,
+    ) -> Result<ListsResponse, ApiError> {
+        configuration.validate()?;
+        let url = format!("{}/lists/create", self.base_url);
+        let request = self.authenticate(self.client.put(url))?.json(configuration);
+        decode_json(self.send(Endpoint::CreateList, request).await?).await
+    }
+    /// Start the download of a list
+    async fn fetch_list(&self, response: &ListsResponse) -> Result<Download, DownloadListError> {
+        self.fetch_list_with(response, |request| request).await
+    }
+    /// Start the download of a list, customizing the request before it is sent
+    async fn fetch_list_with(
+        &self,
+        response: &ListsResponse,
+        customize: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
+    ) -> Result<Download, DownloadListError> {
+        response.check_downloadable()?;
+        let download = self
+            .send_following_redirects(Endpoint::Download, &response.download, |url| {
+                customize(self.client.get(url))
+            })
+            .await?;
+        #[cfg(target_arch = "wasm32")]
+        let download = Download::from(download);
+        Ok(download)
+    }
+    /// Read the next chunk of a download body, waiting for the download throttle if there is one
+    pub(crate) async fn next_chunk(
+        &self,
+        download: &mut Download,
+    ) -> Result<Option<Bytes>, reqwest::Error> {
+        let chunk = download.chunk().await?;
+        if let (Some(throttle), Some(chunk)) = (&self.download_throttle, &chunk) {
+            throttle.acquire_n(chunk.len() as u64).await;
+        }
+        Ok(chunk)
+    }
+    /// Read the rest of a download body, see `next_chunk`
+    async fn read_body(&self, mut download: Download) -> Result<Bytes, reqwest::Error> {
+        if self.download_throttle.is_none() {
+            return download.bytes().await;
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = self.next_chunk(&mut download).await? {
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body.into())
+    }
+    /// Download the full CSV body of a list
+    async fn download_bytes(&self, response: &ListsResponse) -> Result<Bytes, DownloadListError> {
+        self.download_bytes_until(response, None, &RequestOptions::default())
+            .await
+    }
+    /// Like `download_bytes`, abandoning the transfer if `cancel` is cancelled first
+    ///
+    /// Only the transfer is raced against the token, so a cancelled download never reaches the
+    /// cache.
+    async fn download_bytes_until(
+        &self,
+        response: &ListsResponse,
+        cancel: Option<&CancellationToken>,
+        options: &RequestOptions,
+    ) -> Result<Bytes, DownloadListError> {
+        if let Some(csv_body) = self.load_cached(response).await? {
+            return Ok(csv_body);
+        }
+        let transfer = async {
+            let download = self
+                .fetch_list_with(response, |request| options.apply(request))
+                .await?;
+            Ok::<_, DownloadListError>(self.read_body(download).await?)
+        };
+        let csv_body = match cancel {
+            Some(cancel) => cancel
+                .run_until_cancelled(transfer)
+                .await
+                .ok_or(DownloadListError::Cancelled)??,
+            None => transfer.await?,
+        };
+        self.finish_download(response, &csv_body).await?;
+        Ok(csv_body)
+    }
+    /// Account for a downloaded CSV body and cache it
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    async fn finish_download(
+        &self,
+        response: &ListsResponse,
+        csv_body: &[u8],
+    ) -> Result<(), DownloadListError> {
+        self.record_bytes(Endpoint::Download, csv_body.len() as u64);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cache) = &self.cache {
+            cache
+                .store(&response.list_id, response.variant_segment(), csv_body)
+                .await
+                .map_err(DownloadListError::File)?;
+        }
+        Ok(())
+    }
+    /// Read a list from the cache, if there is one and it holds the list
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn load_cached(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<Option<Bytes>, DownloadListError> {
+        match &self.cache {
+            Some(cache) => cache
+                .load(&response.list_id, response.variant_segment())
+                .await
+                .map_err(DownloadListError::File),
+            None => Ok(None),
+        }
+    }
+    /// Read a list from the cache, which browsers don't have a directory for
+    #[cfg(target_arch = "wasm32")]
+    async fn load_cached(
+        &self,
+        _response: &ListsResponse,
+    ) -> Result<Option<Bytes>, DownloadListError> {
+        Ok(None)
+    }
+    /// Download a list
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub async fn download_list(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let csv_body = self.download_bytes(response).await?;
+        Ok(parse_list(&csv_body[..])?)
+    }
+    /// Download a list, overriding the client's configuration for this call, see `download_list`
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `options` - options for the requests of this call
+    pub async fn download_list_with_options(
+        &self,
+        response: &ListsResponse,
+        options: &RequestOptions,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let csv_body = self.download_bytes_until(response, None, options).await?;
+        Ok(parse_list(&csv_body[..])?)
+    }
+    /// Download a list, giving up if `cancel` is cancelled before the transfer completes
+    ///
+    /// Cancelling drops the connection and returns `DownloadListError::Cancelled` without
+    /// caching anything. A list already in the cache is returned regardless.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `cancel` - token to abort the download with
+    pub async fn download_list_with_cancel(
+        &self,
+        response: &ListsResponse,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let csv_body = self
+            .download_bytes_until(response, Some(cancel), &RequestOptions::default())
+            .await?;
+        Ok(parse_list(&csv_body[..])?)
+    }
+    /// Download a list generated with subdomains
+    ///
+    /// Lists requested with `subdomains` set have a third column, so they are parsed separately
+    /// from the rank,domain format of `download_list`. Each format fails with
+    /// `DownloadListError::UnexpectedColumns` when given a line of the other.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub async fn download_subdomain_list(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<Vec<SubdomainEntry>, Error> {
+        let csv_body = self.download_bytes(response).await?;
+        Ok(list_lines(&csv_body[..])
+            .map(|line| parse_subdomain_line(&line?))
+            .collect::<Result<_, DownloadListError>>()?)
+    }
+    /// Download a list, returning the raw CSV alongside the parsed list
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub async fn download_list_with_raw(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<(Vec<RankedDomain>, Bytes), Error> {
+        let csv_body = self.download_bytes(response).await?;
+        let list = parse_list(&csv_body[..])?;
+        Ok((list, csv_body))
+    }
+    /// Download a list, reporting the redirects followed and the URL it came from
+    ///
+    /// The provenance is `None` if the list was read from the cache.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub async fn download_list_with_provenance(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<(Vec<RankedDomain>, Option<DownloadProvenance>), Error> {
+        if let Some(csv_body) = self.load_cached(response).await? {
+            return Ok((parse_list(&csv_body[..])?, None));
+        }
+        let download = self.fetch_list(response).await?;
+        let provenance = DownloadProvenance::of(&download);
+        let csv_body = self.read_body(download).await?;
+        self.finish_download(response, &csv_body).await?;
+        Ok((parse_list(&csv_body[..])?, Some(provenance)))
+    }
+    /// Download a list straight into a map from domain to rank
+    ///
+    /// The CSV is parsed as it streams in, without building an intermediate list. If a domain
+    /// appears more than once, its best (lowest) rank is kept.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub async fn download_rank_map(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<HashMap<String, u64>, Error> {
+        let mut ranks = HashMap::new();
+        self.for_each_line(response, |line| {
+            let ranked = parse_line(line)?;
+            ranks
+                .entry(ranked.domain)
+                .and_modify(|rank: &mut u64| *rank = (*rank).min(ranked.rank))
+                .or_insert(ranked.rank);
+            Ok(())
+        })
+        .await?;
+        Ok(ranks)
+    }
+    /// Download a list, collecting the rows that fail to parse instead of failing
+    ///
+    /// Blank lines and a leading header row are skipped as usual. Only request and transfer
+    /// errors fail the download.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    ///
+    /// # Returns
+    /// The rows that parsed, and the ones that didn't
+    pub async fn download_list_lenient(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<(Vec<RankedDomain>, Vec<BadRow>), Error> {
+        let csv_body = self.download_bytes(response).await?;
+        let mut list = Vec::new();
+        let mut bad_rows = Vec::new();
+        let mut first = true;
+        for (index, line) in csv_body.split(|b| *b == b'\n').enumerate() {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let parsed = match std::str::from_utf8(line) {
+                Ok(line) if is_blank(line) => continue,
+                Ok(line) if std::mem::take(&mut first) && is_header(line) => continue,
+                Ok(line) => parse_line(line),
+                Err(e) => {
+                    first = false;
+                    Err(io::Error::new(io::ErrorKind::InvalidData, e).into())
+                }
+            };
+            match parsed {
+                Ok(ranked) => list.push(ranked),
+                Err(reason) => bad_rows.push(BadRow {
+                    line_number: index + 1,
+                    raw: String::from_utf8_lossy(line).into_owned(),
+                    reason,
+                }),
+            }
+        }
+        Ok((list, bad_rows))
+    }
+    /// Download only the rows of a list whose domain is in a set
+    ///
+    /// The CSV is parsed as it streams in and other rows are dropped right away, so memory use
+    /// depends on the size of `domains` rather than of the list. Domains are compared after
+    /// normalization, ignoring case and a trailing dot.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `domains` - domains to keep
+    pub async fn download_list_filtered(
+        &self,
+        response: &ListsResponse,
+        domains: &HashSet<String>,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let wanted: HashSet<_> = domains
+            .iter()
+            .map(|domain| normalize_domain(domain).into_owned())
+            .collect();
+        let mut list = Vec::new();
+        self.for_each_line(response, |line| {
+            let ranked = parse_line(line)?;
+            if wanted.contains(normalize_domain(&ranked.domain).as_ref()) {
+                list.push(ranked);
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(list)
+    }
+    /// Download every `every_n`th row of a list
+    ///
+    /// The CSV is parsed as it streams in, and only the sampled rows are parsed and kept. The
+    /// first row is always part of the sample.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `every_n` - distance between sampled rows
+    ///
+    /// # Panics
+    /// Panics if `every_n` is 0
+    pub async fn download_list_sampled(
+        &self,
+        response: &ListsResponse,
+        every_n: usize,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        assert!(every_n > 0, "every_n must be positive");
+        let mut sample = Vec::new();
+        let mut index = 0;
+        self.for_each_line(response, |line| {
+            if index % every_n == 0 {
+                sample.push(parse_line(line)?);
+            }
+            index += 1;
+            Ok(())
+        })
+        .await?;
+        Ok(sample)
+    }
+    /// Download a random sample of the rows of a list
+    ///
+    /// Each row is kept independently with the given probability, so the sample size varies
+    /// around `probability` times the size of the list. The same seed always selects the same
+    /// rows of the same list.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `probability` - chance of each row being kept, between 0 and 1
+    /// * `seed` - seed of the random number generator
+    pub async fn download_list_sampled_random(
+        &self,
+        response: &ListsResponse,
+        probability: f64,
+        seed: u64,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let mut sample = Vec::new();
+        let mut state = seed;
+        self.for_each_line(response, |line| {
+            // Top 53 bits of the next output, as a uniform float in [0, 1)
+            let draw = (splitmix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64;
+            if draw < probability {
+                sample.push(parse_line(line)?);
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(sample)
+    }
+    /// Download a list, computing the SHA-256 hash of the raw CSV as it streams in
+    ///
+    /// Tranco doesn't publish checksums for its lists, so to check integrity against a
+    /// previously recorded hash, pass it as `expected`.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `expected` - hash the CSV must have, or `None` to only compute it
+    pub async fn download_list_with_hash(
+        &self,
+        response: &ListsResponse,
+        expected: Option<&[u8; 32]>,
+    ) -> Result<(Vec<RankedDomain>, [u8; 32]), Error> {
+        let mut hasher = Sha256::new();
+        let mut list = Vec::new();
+        self.for_each_chunk_line(
+            response,
+            |chunk| hasher.update(chunk),
+            |line| {
+                list.push(parse_line(line)?);
+                Ok(())
+            },
+        )
+        .await?;
+        let hash: [u8; 32] = hasher.finalize().into();
+        match expected {
+            Some(expected) if *expected != hash => Err(DownloadListError::ChecksumMismatch {
+                expected: *expected,
+                actual: hash,
+            }
+            .into()),
+            _ => Ok((list, hash)),
+        }
+    }
+    /// Download a list, aborting if it exceeds a size limit
+    ///
+    /// The body is streamed and the connection is dropped as soon as the limit is crossed, so at
+    /// most `max_bytes` (plus one chunk) are ever held in memory.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `max_bytes` - maximum size of the CSV body in bytes
+    pub async fn download_list_capped(
+        &self,
+        response: &ListsResponse,
+        max_bytes: u64,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let mut download = self.fetch_list(response).await?;
+        if download.content_length().is_some_and(|len| len > max_bytes) {
+            return Err(DownloadListError::SizeLimitExceeded(max_bytes).into());
+        }
+        let mut csv_body = Vec::new();
+        while let Some(chunk) = self.next_chunk(&mut download).await? {
+            if (csv_body.len() + chunk.len()) as u64 > max_bytes {
+                return Err(DownloadListError::SizeLimitExceeded(max_bytes).into());
+            }
+            csv_body.extend_from_slice(&chunk);
+        }
+        self.record_bytes(Endpoint::Download, csv_body.len() as u64);
+        Ok(parse_list(&csv_body[..])?)
+    }
+    /// Get the size of a list's download in bytes without downloading it
+    ///
+    /// # Parameters
+    /// * `response` - ListsResponse from either `list` or `list_date`
+    ///
+    /// # Returns
+    /// The advertised `Content-Length`, or `None` if the server doesn't report one. With the
+    /// `compression` feature, this may be the compressed size.
+    pub async fn list_download_size(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<Option<u64>, ApiError> {
+        response.check_downloadable()?;
+        let head = self
+            .send_following_redirects(Endpoint::DownloadSize, &response.download, |url| {
+                self.client.head(url)
+            })
+            .await?;
+        Ok(head
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse().ok()))
+    }
+    /// Collect the rank of a domain in every daily list within a date range
+    ///
+    /// Each daily list in the range is downloaded in full, so this is expensive for long ranges.
+    /// Up to `DOWNLOAD_CONCURRENCY` lists are downloaded at a time.
+    ///
+    /// # Parameters
+    /// * `domain` - domain to look up in each list
+    /// * `start` - first date of the range (inclusive)
+    /// * `end` - last date of the range (inclusive)
+    ///
+    /// # Returns
+    /// One entry per date in ascending order, with `None` if the domain was absent from that day's list
+    pub async fn rank_history(
+        &self,
+        domain: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Option<u64>)>, ApiError> {
+        let mut histories = self.rank_history_bulk(&[domain], start, end).await?;
+        Ok(histories.remove(domain).unwrap_or_default())
+    }
+    /// Collect the ranks of several domains in every daily list within a date range
+    ///
+    /// Each daily list is downloaded once and all requested domains are extracted from it, which
+    /// is much cheaper than calling `rank_history` per domain. Up to `DOWNLOAD_CONCURRENCY` lists
+    /// are downloaded at a time.
+    ///
+    /// # Parameters
+    /// * `domains` - domains to look up in each list
+    /// * `start` - first date of the range (inclusive)
+    /// * `end` - last date of the range (inclusive)
+    ///
+    /// # Returns
+    /// The history of each requested domain, in the same form as `rank_history`
+    pub async fn rank_history_bulk(
+        &self,
+        domains: &[&str],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<HashMap<String, Vec<(NaiveDate, Option<u64>)>>, ApiError> {
+        let wanted: HashSet<&str> = domains.iter().copied().collect();
+        let daily: Vec<(NaiveDate, HashMap<String, u64>)> = stream::iter(days(start, end))
+            .map(|date| {
+                let wanted = &wanted;
+                async move { Ok::<_, ApiError>((date, self.domain_ranks_on(wanted, date).await?)) }
+            })
+            .buffered(DOWNLOAD_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(wanted
+            .into_iter()
+            .map(|domain| {
+                let history = daily
+                    .iter()
+                    .map(|(date, ranks)| (*date, ranks.get(domain).copied()))
+                    .collect();
+                (domain.to_string(), history)
+            })
+            .collect())
+    }
+    /// Compare the rank of a domain on two dates
+    ///
+    /// Both daily lists are downloaded in full, concurrently.
+    ///
+    /// # Parameters
+    /// * `domain` - domain to look up in each list
+    /// * `date_a` - first date to compare
+    /// * `date_b` - second date to compare
+    pub async fn compare_domain(
+        &self,
+        domain: &str,
+        date_a: NaiveDate,
+        date_b: NaiveDate,
+    ) -> Result<RankComparison, ApiError> {
+        let (rank_a, rank_b) = future::try_join(
+            self.domain_rank_on(domain, date_a),
+            self.domain_rank_on(domain, date_b),
+        )
+        .await?;
+        Ok(RankComparison {
+            date_a,
+            rank_a,
+            date_b,
+            rank_b,
+        })
+    }
+    /// Get the metadata of the daily list for a date
+    async fn list_on(&self, date: NaiveDate) -> Result<ListsResponse, ApiError> {
+        self.list_date_query(&ListDateQuery::new(date)).await
+    }
+    /// Download the daily list for a date and find a domain's rank in it
+    async fn domain_rank_on(&self, domain: &str, date: NaiveDate) -> Result<Option<u64>, ApiError> {
+        let list = self.list_on(date).await?;
+        Ok(self.rank_in(&list, domain).await?)
+    }
+    /// Get a domain's rank in the daily list of a date
+    ///
+    /// There is no API endpoint for the rank of a domain on a given date, so this downloads the
+    /// whole list (about 1 million rows) and scans it until the domain is found. Configure a
+    /// cache with `with_cache_dir` to look up several domains on the same date without
+    /// downloading the list again each time; for many domains at once, `download_rank_map` is
+    /// cheaper still.
+    ///
+    /// # Parameters
+    /// * `domain` - domain to look up
+    /// * `year`, `month`, `day` - date of the daily list
+    ///
+    /// # Returns
+    /// `None` if the domain isn't in the list
+    pub async fn rank_on_date(
+        &self,
+        domain: &str,
+        year: u16,
+        month: u8,
+        day: u8,
+    ) -> Result<Option<u64>, ApiError> {
+        let list = self.list_date(year, month, day, None).await?;
+        Ok(self.rank_in(&list, domain).await?)
+    }
+    /// Download a list and find a domain's rank in it, stopping at the first match
+    async fn rank_in(
+        &self,
+        response: &ListsResponse,
+        domain: &str,
+    ) -> Result<Option<u64>, DownloadListError> {
+        let csv_body = self.download_bytes(response).await?;
+        for line in list_lines(&csv_body[..]) {
+            let ranked = parse_line(&line?)?;
+            if ranked.domain == domain {
+                return Ok(Some(ranked.rank));
+            }
+        }
+        Ok(None)
+    }
+    /// Download the daily list for a date and find the ranks of several domains in it
+    async fn domain_ranks_on(
+        &self,
+        domains: &HashSet<&str>,
+        date: NaiveDate,
+    ) -> Result<HashMap<String, u64>, ApiError> {
+        let list = self.list_on(date).await?;
+        Ok(self
+            .download_list(&list)
+            .await?
+            .into_iter()
+            .filter(|ranked| domains.contains(ranked.domain.as_str()))
+            .map(|ranked| (ranked.domain, ranked.rank))
+            .collect())
+    }
+}
+
+/// Check that a list includes subdomains if and only if they were requested
+///
+/// Lists with subdomains are generated without the pay-level domain filter, so that's what the
+/// returned configuration is checked against.
+#[cfg(feature = "__client")]
+fn check_subdomains(requested: Option<bool>, response: &ListsResponse) -> Result<(), ApiError> {
+    let Some(requested) = requested else {
+        return Ok(());
+    };
+    let filter_pld = response.configuration.filter_pld.clone();
+    if requested == (filter_pld == ToggleOption::On) {
+        return Err(ApiError::SubdomainFlagMismatch {
+            requested,
+            filter_pld,
+        });
+    }
+    Ok(())
+}
+
+/// Iterate over the dates from `start` to `end` inclusive
+fn days(start: NaiveDate, end: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    start.iter_days().take_while(move |date| *date <= end)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[cfg(feature = "reqwest")]
+    #[error("Error making request: {0}")]
+    Request(#[from] reqwest::Error),
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    #[error("Error from HTTP transport: {0}")]
+    Transport(TransportError),
+    #[cfg(feature = "reqwest")]
+    #[error("Rate limited by the API, retry after {retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+    #[cfg(feature = "reqwest")]
+    #[error("Too many consecutive failures, not sending requests for another {retry_in:?}")]
+    CircuitOpen { retry_in: Duration },
+    #[error("Error downloading list: {0}")]
+    DownloadList(#[from] DownloadListError),
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(url::ParseError),
+    #[error("Response had more than {0} pages")]
+    TooManyPages(u32),
+    #[error("No ranks available for {0}")]
+    NoRanks(String),
+    #[cfg(feature = "reqwest")]
+    #[error("No available list in the {} days tried", .0.len())]
+    NoListFound(Vec<(NaiveDate, SkipReason)>),
+    #[error("Expected a JSON response but got content type {got:?}: {body_snippet}")]
+    UnexpectedContentType {
+        got: Option<String>,
+        body_snippet: String,
+    },
+    #[error("Invalid month {month} of {year}")]
+    InvalidMonth { year: u16, month: u8 },
+    #[error("Malformed date {date:?}: {source}")]
+    MalformedDate {
+        date: String,
+        source: chrono::ParseError,
+    },
+    #[error("Requested subdomains={requested} but got a list with filterPLD {filter_pld:?}")]
+    SubdomainFlagMismatch {
+        requested: bool,
+        filter_pld: ToggleOption,
+    },
+    #[error("Requested list {requested} but got list {returned}")]
+    MismatchedListId { requested: ListId, returned: String },
+    #[error("Invalid list id: {0}")]
+    InvalidListId(#[from] ListIdError),
+    #[error("Error reading ranks CSV: {0}")]
+    RanksCsv(#[from] RanksCsvError),
+    #[error("{0}")]
+    InvalidConfiguration(#[from] ConfigurationError),
+    #[error("Generation of list {0} failed")]
+    ListFailed(ListId),
+    #[error("Request requires credentials, see ClientBuilder::credentials")]
+    MissingCredentials,
+}
+
+/// Crate-wide error type, returned by every method of `Client`
+///
+/// Failures specific to list downloads, e.g. a malformed CSV row or a checksum mismatch, are
+/// kept as a `DownloadListError` in the `ApiError::DownloadList` variant, so they can still be
+/// matched on.
+pub type Error = ApiError;
+
+// The errors must stay usable as `Box<dyn std::error::Error + Send + Sync>`, e.g. with anyhow
+const _: () = {
+    const fn assert_error<E: std::error::Error + Send + Sync + 'static>() {}
+    assert_error::<ApiError>();
+    assert_error::<DownloadListError>();
+};
+
+/// Error sending a request, before it is folded into `ApiError` or `DownloadListError`
+#[cfg(feature = "reqwest")]
+#[derive(Debug)]
+pub(crate) enum SendError {
+    Request(reqwest::Error),
+    #[cfg(not(target_arch = "wasm32"))]
+    Transport(TransportError),
+    /// 429 response, with the delay from its `Retry-After` header
+    RateLimited(Option<Duration>),
+    /// Circuit breaker is open, with the remaining cool-down
+    CircuitOpen(Duration),
+}
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+impl From<TransportError> for SendError {
+    fn from(e: TransportError) -> Self {
+        // Keep reqwest errors, e.g. from the default transport, inspectable
+        match e.downcast::<reqwest::Error>() {
+            Ok(e) => SendError::Request(*e),
+            Err(e) => SendError::Transport(e),
+        }
+    }
+}
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for SendError {
+    fn from(e: reqwest::Error) -> Self {
+        SendError::Request(e)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<SendError> for ApiError {
+    fn from(e: SendError) -> Self {
+        match e {
+            SendError::Request(e) => ApiError::Request(e),
+            #[cfg(not(target_arch = "wasm32"))]
+            SendError::Transport(e) => ApiError::Transport(e),
+            SendError::RateLimited(retry_after) => ApiError::RateLimited { retry_after },
+            SendError::CircuitOpen(retry_in) => ApiError::CircuitOpen { retry_in },
+        }
+    }
+}
+
+/// Decode a JSON API response, checking its content type first
+///
+/// Non-JSON responses (e.g. an HTML error page served with a 200) produce
+/// `ApiError::UnexpectedContentType` with the start of the body for debugging.
+#[cfg(feature = "reqwest")]
+#[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+async fn decode_json<T: DeserializeOwned>(mut response: reqwest::Response) -> Result<T, ApiError> {
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(str::to_owned);
+    let is_json = content_type.as_deref().is_some_and(|content_type| {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        essence.eq_ignore_ascii_case("application/json") || essence.ends_with("+json")
+    });
+    if is_json {
+        return Ok(response.json().await?);
+    }
+    #[cfg(target_arch = "wasm32")]
+    let mut response = Download::from(response);
+    let chunk = response.chunk().await?.unwrap_or_default();
+    let body_snippet = String::from_utf8_lossy(&chunk)
+        .chars()
+        .take(BODY_SNIPPET_LEN)
+        .collect();
+    Err(ApiError::UnexpectedContentType {
+        got: content_type,
+        body_snippet,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadListError {
+    #[cfg(feature = "reqwest")]
+    #[error("Error making request: {0}")]
+    Request(#[from] reqwest::Error),
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    #[error("Error from HTTP transport: {0}")]
+    Transport(TransportError),
+    #[cfg(feature = "reqwest")]
+    #[error("Rate limited by the API, retry after {retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+    #[cfg(feature = "reqwest")]
+    #[error("Too many consecutive failures, not sending requests for another {retry_in:?}")]
+    CircuitOpen { retry_in: Duration },
+    #[error("Download was cancelled")]
+    Cancelled,
+    #[error("Error reading line from csv: {0}")]
+    ReadLine(#[from] io::Error),
+    #[error("CSV is missing rank")]
+    MissingRank,
+    #[error("CSV had invalid rank: {0}")]
+    InvalidRank(#[from] std::num::ParseIntError),
+    #[error("CSV is missing domain")]
+    MissingDomain,
+    #[error("List generation failed")]
+    ListFailed,
+    #[error("List is not available yet")]
+    NotAvailable,
+    #[error("List exceeded the size limit of {0} bytes")]
+    SizeLimitExceeded(u64),
+    #[error("Error accessing downloaded file: {0}")]
+    File(io::Error),
+    #[error(
+        "List checksum mismatch: expected {}, got {}",
+        hex(expected),
+        hex(actual)
+    )]
+    ChecksumMismatch {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
+    #[error("CSV line has {got} columns, expected {expected}")]
+    UnexpectedColumns { expected: usize, got: usize },
+    #[error("List download redirected more than {0} times")]
+    TooManyRedirects(usize),
+    #[error("List download redirected to unexpected host: {0}")]
+    UnexpectedRedirectHost(String),
+    #[error("List download URL on unexpected host: {0}")]
+    UnexpectedDownloadHost(String),
+    #[error("List download redirected from https to http: {0}")]
+    InsecureRedirect(String),
+}
+
+/// Normalize a domain for comparison: surrounding whitespace and a trailing dot are removed,
+/// and it is lowercased
+#[cfg(any(feature = "reqwest", feature = "psl"))]
+fn normalize_domain(domain: &str) -> Cow<'_, str> {
+    let domain = domain.trim().trim_end_matches('.');
+    if domain.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(domain.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(domain)
+    }
+}
+
+/// Next output of the SplitMix64 pseudo-random number generator
+#[cfg(feature = "reqwest")]
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(feature = "reqwest")]
+impl From<SendError> for DownloadListError {
+    fn from(e: SendError) -> Self {
+        match e {
+            SendError::Request(e) => DownloadListError::Request(e),
+            #[cfg(not(target_arch = "wasm32"))]
+            SendError::Transport(e) => DownloadListError::Transport(e),
+            SendError::RateLimited(retry_after) => DownloadListError::RateLimited { retry_after },
+            SendError::CircuitOpen(retry_in) => DownloadListError::CircuitOpen { retry_in },
+        }
+    }
+}
+
+/// Lowercase hexadecimal representation of bytes
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parse the rank,domain CSV format of a downloaded list
+#[cfg(any(feature = "reqwest", feature = "ureq", test))]
+fn parse_list<R: BufRead>(csv_body: R) -> Result<Vec<RankedDomain>, DownloadListError> {
+    list_lines(csv_body)
+        .map(|line| parse_line(&line?))
+        .collect()
+}
+
+/// Lazily parse a window of a rank,domain CSV list, e.g. one page of a list stored on disk
+///
+/// Since lists are ordered by rank, this yields the rows ranked `skip + 1` to `skip + take`.
+/// Rows before the window are skipped without being parsed, and reading stops once the window
+/// is complete, so memory use doesn't depend on the size of the list.
+///
+/// # Parameters
+/// * `csv_body` - reader over a downloaded list
+/// * `skip` - number of rows to skip
+/// * `take` - maximum number of rows to yield
+pub fn parse_list_window<R: BufRead>(
+    csv_body: R,
+    skip: usize,
+    take: usize,
+) -> impl Iterator<Item = Result<RankedDomain, Error>> {
+    list_lines(csv_body)
+        .skip(skip)
+        .take(take)
+        .map(|line| Ok(parse_line(&line.map_err(DownloadListError::ReadLine)?)?))
 }
-impl Client {
-    /// Constructor
-    pub fn new() -> Self {
-        let client = reqwest::Client::new();
-        Self::from_client(client)
+
+/// Iterate over the lines of a CSV list, skipping blank lines and a leading header row
+///
+/// Only the first non-blank line is considered, and it is only treated as a header if its first
+/// field isn't a number.
+fn list_lines<R: BufRead>(csv_body: R) -> impl Iterator<Item = io::Result<String>> {
+    csv_body
+        .lines()
+        .filter(|line| !line.as_deref().is_ok_and(is_blank))
+        .enumerate()
+        .filter(|(i, line)| !(*i == 0 && line.as_deref().is_ok_and(is_header)))
+        .map(|(_, line)| line)
+}
+
+/// Whether a line has no content
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Whether a line is a header rather than a ranked domain
+fn is_header(line: &str) -> bool {
+    line.split(",")
+        .next()
+        .is_some_and(|rank| rank.trim().parse::<u64>().is_err())
+}
+
+/// Parse a single rank,domain line of a list
+///
+/// Whitespace around each field is ignored.
+fn parse_line(line: &str) -> Result<RankedDomain, DownloadListError> {
+    let mut toks = line.split(",").map(str::trim);
+    let rank = toks.next().ok_or(DownloadListError::MissingRank)?.parse()?;
+    let domain = toks.next().ok_or(DownloadListError::MissingDomain)?.into();
+    check_columns(line, 2)?;
+    Ok(RankedDomain { rank, domain })
+}
+
+/// Parse a single rank,pld,subdomain line of a subdomain list
+///
+/// Whitespace around each field is ignored.
+#[cfg(any(feature = "reqwest", test))]
+fn parse_subdomain_line(line: &str) -> Result<SubdomainEntry, DownloadListError> {
+    check_columns(line, 3)?;
+    let mut toks = line.split(",").map(str::trim);
+    let rank = toks.next().ok_or(DownloadListError::MissingRank)?.parse()?;
+    let pld = toks.next().ok_or(DownloadListError::MissingDomain)?.into();
+    let subdomain = toks.next().ok_or(DownloadListError::MissingDomain)?.into();
+    Ok(SubdomainEntry {
+        rank,
+        pld,
+        subdomain,
+    })
+}
+
+/// Check that a line has exactly the expected number of columns
+///
+/// A line with a single column is left to the missing-field errors.
+fn check_columns(line: &str, expected: usize) -> Result<(), DownloadListError> {
+    let got = line.split(",").count();
+    if got > expected || (got > 1 && got < expected) {
+        return Err(DownloadListError::UnexpectedColumns { expected, got });
     }
-    /// Constructor from client
-    pub fn from_client(client: reqwest::Client) -> Self {
-        Self { client }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct RanksResponse {
+    pub ranks: Vec<DomainRank>,
+}
+/// A single page of a ranks response, linking to the next page if there is one
+#[cfg(feature = "__client")]
+#[derive(Deserialize)]
+struct RanksPage {
+    ranks: Vec<DomainRank>,
+    #[serde(default)]
+    next: Option<String>,
+}
+impl RanksResponse {
+    /// Whether there are no ranks at all
+    ///
+    /// The API answers with an empty response both for domains it doesn't know and for domains
+    /// that weren't in any recent daily list, so the two can't be told apart.
+    pub fn is_empty(&self) -> bool {
+        self.ranks.is_empty()
     }
-    /// List ranks for a domain
+    /// Sort the ranks by date, oldest first
     ///
-    /// # Parameters
-    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
-    pub async fn ranks(&self, domain: &str) -> Result<RanksResponse, reqwest::Error> {
-        let url = format!("{API_BASE}/ranks/domain/{domain}");
-        self.client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await
+    /// Dates are compared as strings, which orders `YYYY-MM-DD` dates chronologically. The sort is
+    /// stable, so ranks with the same date keep their relative order.
+    pub fn sort_by_date(&mut self) {
+        self.ranks.sort_by(|a, b| a.date.cmp(&b.date));
     }
-    /// List ranks for a domain
+    /// Iterate over the ranks in windows of at most `size` entries
     ///
-    /// # Parameters
-    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
-    pub async fn list(&self, id: &str) -> Result<ListsResponse, reqwest::Error> {
-        let url = format!("{API_BASE}/lists/id/{id}");
-        self.client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await
+    /// # Panics
+    /// Panics if `size` is 0
+    pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, DomainRank> {
+        self.ranks.chunks(size)
     }
-    /// List ranks for a domain
+    /// One entry per day from `start` to `end` inclusive, with `None` on days the domain was absent
     ///
-    /// # Parameters
-    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
-    pub async fn list_date(
+    /// Entries whose date can't be parsed are treated as absent.
+    pub fn with_gaps_filled(
         &self,
-        year: u16,
-        month: u8,
-        day: u8,
-        subdomains: Option<bool>,
-    ) -> Result<ListsResponse, reqwest::Error> {
-        let url = format!(
-            "{API_BASE}/lists/date/{year:04}{month:02}{day:02}{}",
-            if let Some(subdomains) = subdomains {
-                format!("?subdomains={subdomains}")
-            } else {
-                String::new()
-            }
-        );
-        self.client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(NaiveDate, Option<u64>)> {
+        let by_date: HashMap<NaiveDate, u64> = self
+            .ranks
+            .iter()
+            .filter_map(|rank| Some((rank.parsed_date().ok()?, rank.rank)))
+            .collect();
+        days(start, end)
+            .map(|date| (date, by_date.get(&date).copied()))
+            .collect()
     }
-    /// Download a list
+    /// Map from date to rank, ordered by date
     ///
-    /// # Parameters
-    /// * `list` - ListsResponse from either `list` or `list_date`
-    pub async fn download_list(
-        &self,
-        response: &ListsResponse,
-    ) -> Result<Vec<RankedDomain>, DownloadListError> {
-        let csv_body = self
-            .client
-            .get(response.download.clone())
-            .send()
-            .await?
-            .error_for_status()?
-            .bytes()
-            .await
-            .map(Cursor::new)
-            .map(BufReader::new)?;
-        csv_body
-            .lines()
-            .map(|line| {
-                let line = line?;
-                let mut toks = line.split(",");
-                let rank = toks.next().ok_or(DownloadListError::MissingRank)?.parse()?;
-                let domain = toks.next().ok_or(DownloadListError::MissingDomain)?.into();
-                Ok(RankedDomain { rank, domain })
-            })
+    /// Entries whose date can't be parsed are skipped. If a date appears more than once, the last
+    /// entry for it wins.
+    pub fn by_date(&self) -> BTreeMap<NaiveDate, u64> {
+        self.ranks
+            .iter()
+            .filter_map(|rank| Some((rank.parsed_date().ok()?, rank.rank)))
             .collect()
     }
+    /// Convert into a map from date to rank, ordered by date, see `by_date`
+    pub fn into_by_date(self) -> BTreeMap<NaiveDate, u64> {
+        self.by_date()
+    }
+    /// Write the ranks as a `date,rank` CSV with a header, in date order
+    ///
+    /// Entries whose date can't be parsed are written last, in their original order.
+    pub fn to_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut ranks: Vec<_> = self
+            .ranks
+            .iter()
+            .map(|rank| (rank.parsed_date().ok(), rank))
+            .collect();
+        ranks.sort_by_key(|(date, _)| (date.is_none(), *date));
+        writeln!(writer, "date,rank")?;
+        for (_, rank) in ranks {
+            writeln!(writer, "{},{}", rank.date, rank.rank)?;
+        }
+        Ok(())
+    }
+    /// Read ranks from a `date,rank` CSV, such as one written by `to_csv`
+    ///
+    /// Like list downloads, blank lines are skipped, and so is a header row if the rank field of
+    /// the first non-blank line isn't a number. A UTF-8 byte order mark, as added by some
+    /// spreadsheet applications, is ignored.
+    pub fn from_csv<R: BufRead>(reader: R) -> Result<Self, RanksCsvError> {
+        let mut ranks = Vec::new();
+        let lines = reader.lines().enumerate().map(|(i, line)| match i {
+            0 => line.map(|line| line.trim_start_matches('\u{feff}').into()),
+            _ => line,
+        });
+        let lines = lines.filter(|line| !line.as_deref().is_ok_and(is_blank));
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let mut toks = line.split(",").map(str::trim);
+            let date = toks.next().ok_or(RanksCsvError::MissingDate)?;
+            let rank = toks.next().ok_or(RanksCsvError::MissingRank)?;
+            match rank.parse() {
+                Ok(rank) => ranks.push(DomainRank {
+                    date: date.into(),
+                    rank,
+                }),
+                Err(_) if i == 0 => continue,
+                Err(e) => return Err(RanksCsvError::InvalidRank(e)),
+            }
+        }
+        Ok(Self { ranks })
+    }
 }
 
+/// Error reading a `date,rank` CSV with `RanksResponse::from_csv`
 #[derive(Debug, thiserror::Error)]
-pub enum DownloadListError {
-    #[error("Error making request: {0}")]
-    Request(#[from] reqwest::Error),
+pub enum RanksCsvError {
     #[error("Error reading line from csv: {0}")]
     ReadLine(#[from] io::Error),
+    #[error("CSV is missing date")]
+    MissingDate,
     #[error("CSV is missing rank")]
     MissingRank,
     #[error("CSV had invalid rank: {0}")]
     InvalidRank(#[from] std::num::ParseIntError),
-    #[error("CSV is missing domain")]
-    MissingDomain,
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-pub struct RanksResponse {
-    pub ranks: Vec<DomainRank>,
-}
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 pub struct DomainRank {
     pub date: String,
     pub rank: u64,
 }
+impl DomainRank {
+    /// Parse the date of this rank (format: YYYY-MM-DD)
+    pub fn parsed_date(&self) -> Result<NaiveDate, chrono::ParseError> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d")
+    }
+}
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 pub struct ListsResponse {
     list_id: String,
@@ -145,11 +1742,81 @@ pub struct ListsResponse {
     created_on: String,
     configuration: Configuration,
     failed: bool,
-    jobs_ahead: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_jobs_ahead")]
+    jobs_ahead: Option<u64>,
+}
+impl ListsResponse {
+    /// Id of the list
+    pub fn list_id(&self) -> &str {
+        &self.list_id
+    }
+    /// Whether the list has been generated and can be downloaded
+    pub fn available(&self) -> bool {
+        self.available
+    }
+    /// Whether generation of the list failed
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+    /// Number of lists queued for generation before this one, if the API reported it
+    pub fn jobs_ahead(&self) -> Option<u64> {
+        self.jobs_ahead
+    }
+    /// Parse the time at which the list was created
+    ///
+    /// Timestamps without an offset are interpreted as UTC.
+    pub fn created_on(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        DateTime::parse_from_rfc3339(&self.created_on)
+            .map(|created_on| created_on.to_utc())
+            .or_else(|_| {
+                NaiveDateTime::parse_from_str(&self.created_on, "%Y-%m-%dT%H:%M:%S%.f")
+                    .map(|created_on| created_on.and_utc())
+            })
+    }
+    /// Point the download of this list at a different variant of it
+    ///
+    /// The API always links to the top million domains; this rewrites the download URL to e.g.
+    /// fetch the full ranking instead.
+    pub fn with_variant(mut self, variant: ListVariant) -> Self {
+        if let Some((base, _)) = self.download.rsplit_once('/') {
+            self.download = format!("{base}/{}", variant.path_segment());
+        }
+        self
+    }
+    /// Final segment of the download URL, which names the variant of the list, see `with_variant`
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    fn variant_segment(&self) -> &str {
+        self.download.rsplit('/').next().unwrap_or_default()
+    }
+    /// Configuration the list was generated with
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+    /// Check that the list was generated successfully and can be downloaded
+    #[cfg(feature = "__client")]
+    fn check_downloadable(&self) -> Result<(), DownloadListError> {
+        if self.failed {
+            Err(DownloadListError::ListFailed)
+        } else if !self.available {
+            Err(DownloadListError::NotAvailable)
+        } else {
+            Ok(())
+        }
+    }
+    /// Time elapsed between the creation of the list and now
+    pub fn age(&self) -> Result<TimeDelta, chrono::ParseError> {
+        Ok(Utc::now() - self.created_on()?)
+    }
+    /// Order lists from most to least recently created
+    ///
+    /// Lists whose creation time can't be parsed sort last. Intended for use with `sort_by`.
+    pub fn cmp_recency(&self, other: &Self) -> Ordering {
+        other.created_on().ok().cmp(&self.created_on().ok())
+    }
 }
 
 /// Represents a configuration for domain aggregation and filtering
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Configuration {
     /// List of data providers to use
@@ -170,20 +1837,24 @@ pub struct Configuration {
     pub inclusion_days: ToggleOption,
     /// Minimum number of days domains must be present
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inclusion_days_value: Option<u32>,
     /// Whether to only include domains present in a minimum number of lists
     #[serde(default)]
     pub inclusion_lists: ToggleOption,
     /// Minimum number of lists domains must be present in
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inclusion_lists_value: Option<u32>,
     /// TLD filtering mode
     #[serde(default)]
     #[serde(rename = "filterTLD")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_tld: Option<FilterTldOption>,
     /// TLDs to retain if filter_tld is Include
     #[serde(default)]
     #[serde(rename = "filterTLDValue")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_tld_value: Option<Vec<String>>,
     /// Whether to retain only one domain per organization
     #[serde(default)]
@@ -193,6 +1864,7 @@ pub struct Configuration {
     pub filter_subdomain: ToggleOption,
     /// Subdomains to retain if filter_subdomain is On
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_subdomain_value: Option<Vec<String>>,
     /// Whether to filter out Google Safe Browsing domains
     #[serde(default)]
@@ -204,36 +1876,229 @@ pub struct Configuration {
     /// Month of CrUX data, or latest available month
     #[serde(default)]
     #[serde(rename = "filterCRUXMonth")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_crux_month: Option<CruxMonth>,
     /// Type of selected CrUX dataset
     #[serde(default)]
     #[serde(rename = "filterCRUXType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_crux_type: Option<CruxType>,
     /// Value for selected CrUX dataset (except "global")
     #[serde(default)]
     #[serde(rename = "filterCRUXValue")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_crux_value: Option<Vec<String>>,
 }
+impl Configuration {
+    /// Configuration using the given providers, with Tranco's defaults for everything else
+    ///
+    /// Like the default Tranco list, this covers the 30 days up to yesterday (in UTC), combines
+    /// rankings with the Dowdall rule over the full provider lists, and keeps only pay-level
+    /// domains. Every other filter is off.
+    pub fn with_providers(providers: Vec<Provider>) -> Configuration {
+        let end = Utc::now().date_naive() - TimeDelta::days(1);
+        let start = end - TimeDelta::days(29);
+        Configuration {
+            providers,
+            start_date: start.format("%Y-%m-%d").to_string(),
+            end_date: end.format("%Y-%m-%d").to_string(),
+            combination_method: CombinationMethod::Dowdall,
+            list_prefix: ListPrefix::Full,
+            filter_pld: ToggleOption::On,
+            inclusion_days: ToggleOption::Off,
+            inclusion_days_value: None,
+            inclusion_lists: ToggleOption::Off,
+            inclusion_lists_value: None,
+            filter_tld: None,
+            filter_tld_value: None,
+            filter_organization: ToggleOption::Off,
+            filter_subdomain: ToggleOption::Off,
+            filter_subdomain_value: None,
+            filter_safe_browsing: ToggleOption::Off,
+            filter_crux: ToggleOption::Off,
+            filter_crux_month: None,
+            filter_crux_type: None,
+            filter_crux_value: None,
+        }
+    }
+    /// Fields whose value differs between two configurations, in declaration order
+    pub fn diff(&self, other: &Configuration) -> Vec<ConfigFieldDiff> {
+        let mut diffs = Vec::new();
+        macro_rules! diff_fields {
+            ($($field:ident => $name:literal,)*) => {
+                $(
+                    if self.$field != other.$field {
+                        diffs.push(ConfigFieldDiff {
+                            field: $name,
+                            left: format!("{:?}", self.$field),
+                            right: format!("{:?}", other.$field),
+                        });
+                    }
+                )*
+            };
+        }
+        diff_fields! {
+            providers => "providers",
+            start_date => "startDate",
+            end_date => "endDate",
+            combination_method => "combinationMethod",
+            list_prefix => "listPrefix",
+            filter_pld => "filterPLD",
+            inclusion_days => "inclusionDays",
+            inclusion_days_value => "inclusionDaysValue",
+            inclusion_lists => "inclusionLists",
+            inclusion_lists_value => "inclusionListsValue",
+            filter_tld => "filterTLD",
+            filter_tld_value => "filterTLDValue",
+            filter_organization => "filterOrganization",
+            filter_subdomain => "filterSubdomain",
+            filter_subdomain_value => "filterSubdomainValue",
+            filter_safe_browsing => "filterSafeBrowsing",
+            filter_crux => "filterCRUX",
+            filter_crux_month => "filterCRUXMonth",
+            filter_crux_type => "filterCRUXType",
+            filter_crux_value => "filterCRUXValue",
+        }
+        diffs
+    }
+    /// Check the invariants between fields that depend on each other
+    ///
+    /// Every violation is collected rather than stopping at the first one.
+    pub fn validate(&self) -> Result<(), ConfigurationError> {
+        fn is_empty(values: &Option<Vec<String>>) -> bool {
+            values.as_ref().is_none_or(Vec::is_empty)
+        }
+        let mut violations = Vec::new();
+        if self.inclusion_days == ToggleOption::On && self.inclusion_days_value.is_none() {
+            violations.push(ConfigurationViolation::MissingInclusionDaysValue);
+        }
+        if self.inclusion_lists == ToggleOption::On && self.inclusion_lists_value.is_none() {
+            violations.push(ConfigurationViolation::MissingInclusionListsValue);
+        }
+        if self.filter_tld == Some(FilterTldOption::Include) && is_empty(&self.filter_tld_value) {
+            violations.push(ConfigurationViolation::MissingTldValue);
+        }
+        if self.filter_subdomain == ToggleOption::On && is_empty(&self.filter_subdomain_value) {
+            violations.push(ConfigurationViolation::MissingSubdomainValue);
+        }
+        if self.filter_crux == ToggleOption::On {
+            match &self.filter_crux_type {
+                None => violations.push(ConfigurationViolation::MissingCruxType),
+                Some(CruxType::Global) => {
+                    if !is_empty(&self.filter_crux_value) {
+                        violations.push(ConfigurationViolation::UnexpectedCruxValue);
+                    }
+                }
+                Some(crux_type) => {
+                    if is_empty(&self.filter_crux_value) {
+                        violations
+                            .push(ConfigurationViolation::MissingCruxValue(crux_type.clone()));
+                    }
+                }
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigurationError(violations))
+        }
+    }
+}
 
-/// Supported data providers
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum Provider {
-    Crux,
-    Majestic,
-    Radar,
-    Umbrella,
-    Alexa,
-    Quantcast,
-    Farsight,
+/// A field that differs between two configurations, from `Configuration::diff`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigFieldDiff {
+    /// Name of the field as used by the Tranco API, e.g. `filterPLD`
+    pub field: &'static str,
+    /// Value in the configuration `diff` was called on
+    pub left: String,
+    /// Value in the other configuration
+    pub right: String,
 }
 
-/// Methods for combining rankings from different providers
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum CombinationMethod {
-    Dowdall,
-    Borda,
+/// Error returned by `Configuration::validate`, listing every violated invariant
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("Invalid configuration: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+pub struct ConfigurationError(pub Vec<ConfigurationViolation>);
+
+/// A single invariant violated by a `Configuration`
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum ConfigurationViolation {
+    #[error("inclusionDays is on but inclusionDaysValue is missing")]
+    MissingInclusionDaysValue,
+    #[error("inclusionLists is on but inclusionListsValue is missing")]
+    MissingInclusionListsValue,
+    #[error("filterTLD is include but filterTLDValue is empty")]
+    MissingTldValue,
+    #[error("filterSubdomain is on but filterSubdomainValue is empty")]
+    MissingSubdomainValue,
+    #[error("filterCRUX is on but filterCRUXType is missing")]
+    MissingCruxType,
+    #[error("filterCRUXType is {0:?} but filterCRUXValue is empty")]
+    MissingCruxValue(CruxType),
+    #[error("filterCRUXType is global but filterCRUXValue is set")]
+    UnexpectedCruxValue,
+}
+
+/// Define a fieldless enum together with its wire strings
+///
+/// Generates the enum with a serde rename per variant, `all()` listing every variant in
+/// declaration order, `as_str()` returning the wire string, and a matching `Display`.
+macro_rules! wire_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $wire:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $(
+                #[serde(rename = $wire)]
+                $variant,
+            )*
+        }
+        impl $name {
+            /// Every variant, in declaration order
+            pub fn all() -> &'static [$name] {
+                &[$($name::$variant),*]
+            }
+            /// Wire representation used by the Tranco API
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $wire,)*
+                }
+            }
+        }
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+wire_enum! {
+    /// Supported data providers
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub enum Provider {
+        Crux => "crux",
+        Majestic => "majestic",
+        Radar => "radar",
+        Umbrella => "umbrella",
+        Alexa => "alexa",
+        Quantcast => "quantcast",
+        Farsight => "farsight",
+    }
+}
+
+wire_enum! {
+    /// Methods for combining rankings from different providers
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub enum CombinationMethod {
+        Dowdall => "dowdall",
+        Borda => "borda",
+    }
 }
 
 /// Options for list_prefix field
@@ -244,7 +2109,7 @@ pub enum ListPrefix {
 }
 
 /// Toggle options (on/off)
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ToggleOption {
     On,
@@ -259,7 +2124,7 @@ impl Default for ToggleOption {
 }
 
 /// Filter TLD options
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FilterTldOption {
     Include,
@@ -273,14 +2138,15 @@ pub enum CruxMonth {
     Specific(String), // Format: YYYYMM
 }
 
-/// Type of CrUX dataset to filter on
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum CruxType {
-    Global,
-    Country,
-    Region,
-    Subregion,
+wire_enum! {
+    /// Type of CrUX dataset to filter on
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub enum CruxType {
+        Global => "global",
+        Country => "country",
+        Region => "region",
+        Subregion => "subregion",
+    }
 }
 
 // Custom implementation for deserialization of ListPrefix
@@ -321,6 +2187,94 @@ impl<'de> Deserialize<'de> for ListPrefix {
     }
 }
 
+// Custom implementation for serialization of ListPrefix, mirroring its deserialization
+impl Serialize for ListPrefix {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            ListPrefix::Full => serializer.serialize_str("full"),
+            ListPrefix::Length(length) => serializer.serialize_u32(*length),
+        }
+    }
+}
+
+// Custom deserialization of jobs_ahead, tolerating the loose typing of the field: integers,
+// numbers, numeric strings, and null are accepted, and negative values map to None
+fn deserialize_jobs_ahead<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct JobsAheadVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for JobsAheadVisitor {
+        type Value = Option<u64>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a number, a numeric string, or null")
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Option<u64>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(Some(value))
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Option<u64>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(u64::try_from(value).ok())
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Option<u64>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok((value.is_finite() && value >= 0.0).then_some(value as u64))
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Option<u64>, E>
+        where
+            E: serde::de::Error,
+        {
+            let value = value.trim();
+            if let Ok(value) = value.parse::<i64>() {
+                self.visit_i64(value)
+            } else if let Ok(value) = value.parse::<f64>() {
+                self.visit_f64(value)
+            } else {
+                Err(E::custom(format!("Expected a numeric string, got {value}")))
+            }
+        }
+
+        fn visit_none<E>(self) -> Result<Option<u64>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Option<u64>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_some<D>(self, deserializer: D) -> Result<Option<u64>, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    deserializer.deserialize_any(JobsAheadVisitor)
+}
+
 // Custom implementation for deserialization of CruxMonth
 impl<'de> Deserialize<'de> for CruxMonth {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -344,7 +2298,7 @@ impl<'de> Deserialize<'de> for CruxMonth {
                     Ok(CruxMonth::Latest)
                 } else {
                     // Validate YYYYMM format
-                    if value.len() == 6 && value.chars().all(|c| c.is_digit(10)) {
+                    if value.len() == 6 && value.chars().all(|c| c.is_ascii_digit()) {
                         Ok(CruxMonth::Specific(value.to_string()))
                     } else {
                         Err(E::custom(format!(
@@ -360,8 +2314,257 @@ impl<'de> Deserialize<'de> for CruxMonth {
     }
 }
 
+// Custom implementation for serialization of CruxMonth, mirroring its deserialization
+impl Serialize for CruxMonth {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            CruxMonth::Latest => serializer.serialize_str("latest"),
+            CruxMonth::Specific(month) => serializer.serialize_str(month),
+        }
+    }
+}
+
+/// A domain's rank on two dates, from `Client::compare_domain`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RankComparison {
+    pub date_a: NaiveDate,
+    /// Rank on `date_a`, or `None` if the domain was absent from that list
+    pub rank_a: Option<u64>,
+    pub date_b: NaiveDate,
+    /// Rank on `date_b`, or `None` if the domain was absent from that list
+    pub rank_b: Option<u64>,
+}
+impl RankComparison {
+    /// Change in rank from `date_a` to `date_b`
+    ///
+    /// Negative values mean the domain moved up the list. `None` if the domain is absent on either date.
+    pub fn delta(&self) -> Option<i64> {
+        Some(self.rank_b? as i64 - self.rank_a? as i64)
+    }
+    /// How the domain's standing on `date_a` compares to `date_b`
+    ///
+    /// `Ordering::Greater` means it ranked better (a lower rank) on `date_a`. Being present in a
+    /// list counts as better than being absent from it.
+    pub fn standing(&self) -> Ordering {
+        match (self.rank_a, self.rank_b) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+/// Change in a domain's rank from one daily list to the next, see `Client::latest_rank_change`
 #[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RankDelta {
+    /// Date of the more recent list
+    pub date: NaiveDate,
+    pub previous: u64,
+    pub current: u64,
+    /// `current - previous`; negative values mean the domain moved up the list
+    pub change: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RankedDomain {
     pub rank: u64,
     pub domain: String,
 }
+impl RankedDomain {
+    /// View of this entry serialized as `{"position": ..., "host": ...}`
+    pub fn view(&self) -> RankedDomainView<'_> {
+        RankedDomainView {
+            position: self.rank,
+            host: &self.domain,
+        }
+    }
+}
+
+/// Borrowed `RankedDomain` serialized with the `position` and `host` field names some tools
+/// expect, see `RankedDomain::view`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct RankedDomainView<'a> {
+    pub position: u64,
+    pub host: &'a str,
+}
+
+/// Row of a list that failed to parse, from `Client::download_list_lenient`
+#[derive(Debug)]
+pub struct BadRow {
+    /// 1-based line number of the row in the CSV
+    pub line_number: usize,
+    /// Content of the line, with invalid UTF-8 replaced
+    pub raw: String,
+    /// Why the row failed to parse
+    pub reason: DownloadListError,
+}
+
+/// Entry of a list generated with subdomains, which has a rank,pld,subdomain format
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubdomainEntry {
+    pub rank: u64,
+    /// Pay-level domain the subdomain belongs to
+    pub pld: String,
+    pub subdomain: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(rank: u64, domain: &str) -> RankedDomain {
+        RankedDomain {
+            rank,
+            domain: domain.into(),
+        }
+    }
+
+    #[test]
+    fn parse_list_without_header() {
+        let list = parse_list(&b"1,google.com\n2,facebook.com\n"[..]).unwrap();
+        assert_eq!(list, [ranked(1, "google.com"), ranked(2, "facebook.com")]);
+    }
+
+    #[test]
+    fn parse_list_with_header() {
+        let list = parse_list(&b"rank,domain\n1,google.com\n2,facebook.com\n"[..]).unwrap();
+        assert_eq!(list, [ranked(1, "google.com"), ranked(2, "facebook.com")]);
+    }
+
+    #[test]
+    fn parse_list_with_blank_line_before_header() {
+        let list = parse_list(&b"\r\n  \nrank,domain\r\n1,google.com\r\n\n"[..]).unwrap();
+        assert_eq!(list, [ranked(1, "google.com")]);
+    }
+
+    #[test]
+    fn parse_list_only_skips_leading_header() {
+        let error = parse_list(&b"1,google.com\nrank,domain\n"[..]).unwrap_err();
+        assert!(matches!(error, DownloadListError::InvalidRank(_)));
+    }
+
+    #[test]
+    fn parse_line_trims_fields() {
+        assert_eq!(
+            parse_line(" 1 , google.com ").unwrap(),
+            ranked(1, "google.com")
+        );
+        assert_eq!(
+            parse_line("\t2,\tfacebook.com").unwrap(),
+            ranked(2, "facebook.com")
+        );
+    }
+
+    #[test]
+    fn parse_list_trims_fields() {
+        let list = parse_list(&b" rank , domain \n 1 , google.com \n"[..]).unwrap();
+        assert_eq!(list, [ranked(1, "google.com")]);
+    }
+
+    #[test]
+    fn parse_subdomain_line_trims_fields() {
+        assert_eq!(
+            parse_subdomain_line(" 1 , google.com , mail.google.com ").unwrap(),
+            SubdomainEntry {
+                rank: 1,
+                pld: "google.com".into(),
+                subdomain: "mail.google.com".into(),
+            }
+        );
+    }
+
+    #[derive(Deserialize)]
+    struct JobsAhead {
+        #[serde(default, deserialize_with = "deserialize_jobs_ahead")]
+        jobs_ahead: Option<u64>,
+    }
+
+    fn jobs_ahead(json: &str) -> Option<u64> {
+        serde_json::from_str::<JobsAhead>(json).unwrap().jobs_ahead
+    }
+
+    #[test]
+    fn jobs_ahead_shapes() {
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": 3}"#), Some(3));
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": -1}"#), None);
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": 2.0}"#), Some(2));
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": 2.7}"#), Some(2));
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": -0.5}"#), None);
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": "5"}"#), Some(5));
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": " 5 "}"#), Some(5));
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": "-5"}"#), None);
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": "1.5"}"#), Some(1));
+        assert_eq!(jobs_ahead(r#"{"jobs_ahead": null}"#), None);
+        assert_eq!(jobs_ahead(r#"{}"#), None);
+    }
+
+    #[test]
+    fn jobs_ahead_rejects_non_numeric_string() {
+        assert!(serde_json::from_str::<JobsAhead>(r#"{"jobs_ahead": "soon"}"#).is_err());
+    }
+
+    #[test]
+    fn ranked_domain_round_trip() {
+        let domain = ranked(1, "google.com");
+        let json = serde_json::to_string(&domain).unwrap();
+        assert_eq!(json, r#"{"rank":1,"domain":"google.com"}"#);
+        assert_eq!(serde_json::from_str::<RankedDomain>(&json).unwrap(), domain);
+    }
+
+    #[test]
+    fn ranked_domain_view_field_names() {
+        let domain = ranked(1, "google.com");
+        assert_eq!(
+            serde_json::to_value(domain.view()).unwrap(),
+            serde_json::json!({"position": 1, "host": "google.com"})
+        );
+    }
+
+    #[test]
+    fn sort_by_date_shuffled() {
+        let rank = |date: &str, rank| DomainRank {
+            date: date.into(),
+            rank,
+        };
+        let mut ranks = RanksResponse {
+            ranks: vec![
+                rank("2024-03-02", 3),
+                rank("2023-12-31", 1),
+                rank("2024-03-10", 4),
+                rank("2024-01-01", 2),
+                rank("2024-03-02", 5),
+            ],
+        };
+        ranks.sort_by_date();
+        assert_eq!(
+            ranks.ranks,
+            [
+                rank("2023-12-31", 1),
+                rank("2024-01-01", 2),
+                rank("2024-03-02", 3),
+                rank("2024-03-02", 5),
+                rank("2024-03-10", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn ranks_from_csv_skips_header_after_blank_lines_and_bom() {
+        let csv = "\u{feff}\n\r\ndate,rank\n2024-03-02,3\n\n2024-03-03,4\n";
+        let ranks = RanksResponse::from_csv(csv.as_bytes()).unwrap();
+        let dates: Vec<_> = ranks.ranks.iter().map(|rank| rank.date.as_str()).collect();
+        assert_eq!(dates, ["2024-03-02", "2024-03-03"]);
+        let ranks = RanksResponse::from_csv("\u{feff}2024-03-02,3\n".as_bytes()).unwrap();
+        assert_eq!(ranks.ranks[0].date, "2024-03-02");
+    }
+
+    #[test]
+    fn ranks_from_csv_only_skips_leading_header() {
+        let error = RanksResponse::from_csv("2024-03-02,3\ndate,rank\n".as_bytes()).unwrap_err();
+        assert!(matches!(error, RanksCsvError::InvalidRank(_)));
+    }
+}
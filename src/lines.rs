@@ -0,0 +1,284 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "reqwest")]
+use crate::download::Download;
+#[cfg(feature = "reqwest")]
+use crate::{Client, Endpoint, Error, ListsResponse, RankedDomain, parse_line};
+use crate::{DownloadListError, is_blank, is_header};
+#[cfg(feature = "reqwest")]
+use futures_util::{Stream, TryStreamExt, stream};
+use std::io;
+
+/// Splits a CSV body into lines as chunks of it arrive, skipping blank lines and a leading header
+/// row
+///
+/// Lines are decoded as UTF-8 with any trailing `\r` removed, matching `BufRead::lines`.
+pub(crate) struct LineSplitter {
+    partial: Vec<u8>,
+    first: bool,
+}
+impl LineSplitter {
+    /// Constructor
+    pub(crate) fn new() -> Self {
+        Self::with_buffer(Vec::new())
+    }
+    /// Reuse `partial`'s allocation to hold partial lines
+    pub(crate) fn with_buffer(mut partial: Vec<u8>) -> Self {
+        partial.clear();
+        Self {
+            partial,
+            first: true,
+        }
+    }
+    /// Take back the buffer used for partial lines
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn into_buffer(self) -> Vec<u8> {
+        self.partial
+    }
+    /// Feed the next chunk of the body, handing every complete data line to `f`
+    pub(crate) fn push(
+        &mut self,
+        chunk: &[u8],
+        f: &mut impl FnMut(&str) -> Result<(), DownloadListError>,
+    ) -> Result<(), DownloadListError> {
+        self.partial.extend_from_slice(chunk);
+        let mut start = 0;
+        while let Some(len) = self.partial[start..].iter().position(|b| *b == b'\n') {
+            let end = start + len;
+            Self::line(&mut self.first, f, &self.partial[start..end])?;
+            start = end + 1;
+        }
+        self.partial.drain(..start);
+        Ok(())
+    }
+    /// Hand the final line to `f` if the body doesn't end with a newline
+    pub(crate) fn finish(
+        &mut self,
+        f: &mut impl FnMut(&str) -> Result<(), DownloadListError>,
+    ) -> Result<(), DownloadListError> {
+        if self.partial.is_empty() {
+            return Ok(());
+        }
+        Self::line(&mut self.first, f, &self.partial)?;
+        self.partial.clear();
+        Ok(())
+    }
+    fn line(
+        first: &mut bool,
+        f: &mut impl FnMut(&str) -> Result<(), DownloadListError>,
+        line: &[u8],
+    ) -> Result<(), DownloadListError> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line =
+            std::str::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if is_blank(line) || (std::mem::take(first) && is_header(line)) {
+            return Ok(());
+        }
+        f(line)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Client {
+    /// Stream a list download, handing each data line to `f` as it arrives
+    ///
+    /// Only one chunk of the body and one partial line are held in memory at a time.
+    pub(crate) async fn for_each_line(
+        &self,
+        response: &ListsResponse,
+        f: impl FnMut(&str) -> Result<(), DownloadListError>,
+    ) -> Result<(), DownloadListError> {
+        self.for_each_chunk_line(response, |_| (), f).await
+    }
+    /// Stream a list download, handing each raw chunk to `on_chunk` before splitting it into
+    /// lines for `f`
+    pub(crate) async fn for_each_chunk_line(
+        &self,
+        response: &ListsResponse,
+        on_chunk: impl FnMut(&[u8]),
+        f: impl FnMut(&str) -> Result<(), DownloadListError>,
+    ) -> Result<(), DownloadListError> {
+        self.for_each_chunk_line_in(&mut Vec::new(), response, on_chunk, f)
+            .await
+    }
+    /// Like `for_each_chunk_line`, holding partial lines in `buffer` so its allocation can be
+    /// reused across downloads
+    pub(crate) async fn for_each_chunk_line_in(
+        &self,
+        buffer: &mut Vec<u8>,
+        response: &ListsResponse,
+        mut on_chunk: impl FnMut(&[u8]),
+        mut f: impl FnMut(&str) -> Result<(), DownloadListError>,
+    ) -> Result<(), DownloadListError> {
+        let mut lines = LineSplitter::with_buffer(std::mem::take(buffer));
+        if let Some(csv_body) = self.load_cached(response).await? {
+            on_chunk(&csv_body);
+            lines.push(&csv_body, &mut f)?;
+        } else {
+            let mut download = self.fetch_list(response).await?;
+            let mut bytes = 0;
+            while let Some(chunk) = self.next_chunk(&mut download).await? {
+                bytes += chunk.len() as u64;
+                on_chunk(&chunk);
+                lines.push(&chunk, &mut f)?;
+            }
+            self.record_bytes(Endpoint::Download, bytes);
+        }
+        lines.finish(&mut f)?;
+        *buffer = lines.into_buffer();
+        Ok(())
+    }
+    /// Download a list as a stream of ranked domains
+    ///
+    /// The CSV is parsed as it streams in, so only one chunk of the body is held in memory at a
+    /// time. Dropping the stream before it ends drops the response with it, which closes the
+    /// connection rather than returning it half-read to the pool, so later requests on the same
+    /// client are unaffected.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub fn download_list_stream<'a>(
+        &'a self,
+        response: &'a ListsResponse,
+    ) -> impl Stream<Item = Result<RankedDomain, Error>> + 'a {
+        stream::try_unfold(StreamState::Start, move |state| {
+            self.next_stream_batch(response, state)
+        })
+        .map_ok(|batch| stream::iter(batch.into_iter().map(Ok)))
+        .try_flatten()
+    }
+    /// Parse the next chunk of a streaming download, see `download_list_stream`
+    pub(crate) async fn next_stream_batch(
+        &self,
+        response: &ListsResponse,
+        state: StreamState,
+    ) -> Result<Option<(Vec<RankedDomain>, StreamState)>, DownloadListError> {
+        let mut batch = Vec::new();
+        let mut parse = |line: &str| {
+            batch.push(parse_line(line)?);
+            Ok(())
+        };
+        let (mut download, mut lines, mut bytes) = match state {
+            StreamState::Start => {
+                if let Some(csv_body) = self.load_cached(response).await? {
+                    let mut lines = LineSplitter::new();
+                    lines.push(&csv_body, &mut parse)?;
+                    lines.finish(&mut parse)?;
+                    return Ok(Some((batch, StreamState::Done)));
+                }
+                (self.fetch_list(response).await?, LineSplitter::new(), 0)
+            }
+            StreamState::Downloading(download, lines, bytes) => (download, lines, bytes),
+            StreamState::Done => return Ok(None),
+        };
+        match self.next_chunk(&mut download).await? {
+            Some(chunk) => {
+                bytes += chunk.len() as u64;
+                lines.push(&chunk, &mut parse)?;
+                Ok(Some((
+                    batch,
+                    StreamState::Downloading(download, lines, bytes),
+                )))
+            }
+            None => {
+                self.record_bytes(Endpoint::Download, bytes);
+                lines.finish(&mut parse)?;
+                Ok(Some((batch, StreamState::Done)))
+            }
+        }
+    }
+}
+
+/// Progress of a streaming download
+#[cfg(feature = "reqwest")]
+pub(crate) enum StreamState {
+    Start,
+    /// Body being read, with the lines split so far and the number of bytes read
+    Downloading(Download, LineSplitter, u64),
+    Done,
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::Configuration;
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const ROWS: usize = 100_000;
+
+    /// Answer every request on a connection with `body`, until the client hangs up
+    async fn serve(mut socket: TcpStream, body: Arc<String>) {
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        loop {
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            request.clear();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/csv\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            );
+            if socket.write_all(head.as_bytes()).await.is_err()
+                || socket.write_all(body.as_bytes()).await.is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn dropped_stream_leaves_client_usable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let body: Arc<String> = Arc::new(
+            (1..=ROWS)
+                .map(|rank| format!("{rank},domain{rank}.com\n"))
+                .collect(),
+        );
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket, Arc::clone(&body)));
+            }
+        });
+        let client = Client::builder()
+            .base_url(format!("http://{addr}/api"))
+            .build();
+        let response = ListsResponse {
+            list_id: "TEST".into(),
+            available: true,
+            download: format!("http://{addr}/download/TEST/1000000"),
+            created_on: "2024-01-01T00:00:00".into(),
+            configuration: Configuration::with_providers(Vec::new()),
+            failed: false,
+            jobs_ahead: None,
+        };
+
+        let mut stream = Box::pin(client.download_list_stream(&response));
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.rank, 1);
+        drop(stream);
+
+        let list = tokio::time::timeout(Duration::from_secs(10), client.download_list(&response))
+            .await
+            .expect("request after a dropped stream hung")
+            .unwrap();
+        assert_eq!(list.len(), ROWS);
+        assert_eq!(list[ROWS - 1].domain, format!("domain{ROWS}.com"));
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::lines::StreamState;
+use crate::{Client, Error, ListsResponse, RankedDomain};
+use futures_util::{Stream, TryStreamExt, stream};
+use std::fmt;
+use std::ops::Deref;
+
+/// Metadata of a list together with the client to download it with
+///
+/// Returned by `Client::list` and `Client::list_date`, so that the list can be inspected before
+/// deciding whether to download it. Derefs to the `ListsResponse` it wraps.
+#[derive(Clone)]
+pub struct List {
+    client: Client,
+    metadata: ListsResponse,
+}
+
+impl List {
+    /// Constructor
+    pub(crate) fn new(client: Client, metadata: ListsResponse) -> Self {
+        Self { client, metadata }
+    }
+    /// Metadata of the list
+    pub fn metadata(&self) -> &ListsResponse {
+        &self.metadata
+    }
+    /// Unwrap into the metadata of the list
+    pub fn into_metadata(self) -> ListsResponse {
+        self.metadata
+    }
+    /// Download the list, see `Client::download_list`
+    pub async fn download(self) -> Result<Vec<RankedDomain>, Error> {
+        self.client.download_list(&self.metadata).await
+    }
+    /// Download the list as a stream of ranked domains, see `Client::download_list_stream`
+    pub fn download_stream(self) -> impl Stream<Item = Result<RankedDomain, Error>> {
+        stream::try_unfold((self, StreamState::Start), |(list, state)| async move {
+            let next = list.client.next_stream_batch(&list.metadata, state).await;
+            next.map_err(Error::from)
+                .map(|next| next.map(|(batch, state)| (batch, (list, state))))
+        })
+        .map_ok(|batch| stream::iter(batch.into_iter().map(Ok)))
+        .try_flatten()
+    }
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("List")
+            .field("metadata", &self.metadata)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for List {
+    type Target = ListsResponse;
+
+    fn deref(&self) -> &Self::Target {
+        &self.metadata
+    }
+}
+
+impl From<List> for ListsResponse {
+    fn from(list: List) -> Self {
+        list.metadata
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifier of a Tranco list, such as `LJL44`
+///
+/// List ids are made of uppercase ASCII letters and digits. Tranco doesn't document their
+/// length, so any non-empty id is accepted.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[serde(try_from = "String")]
+pub struct ListId(String);
+
+impl ListId {
+    /// The id as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    /// Wrap an id returned by the API as is
+    ///
+    /// The API is the authority on its own ids, so one it hands out is used even if it doesn't
+    /// look like the ids users are expected to type.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn from_api(id: &str) -> Self {
+        Self(id.into())
+    }
+}
+
+impl FromStr for ListId {
+    type Err = ListIdError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        if id.is_empty() {
+            return Err(ListIdError::Empty);
+        }
+        if let Some(c) = id
+            .chars()
+            .find(|c| !(c.is_ascii_uppercase() || c.is_ascii_digit()))
+        {
+            return Err(ListIdError::Character(c));
+        }
+        Ok(Self(id.into()))
+    }
+}
+
+impl TryFrom<&str> for ListId {
+    type Error = ListIdError;
+
+    fn try_from(id: &str) -> Result<Self, Self::Error> {
+        id.parse()
+    }
+}
+
+impl TryFrom<String> for ListId {
+    type Error = ListIdError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        id.parse()
+    }
+}
+
+impl AsRef<str> for ListId {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for ListId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum ListIdError {
+    #[error("List id is empty")]
+    Empty,
+    #[error("List id contains invalid character {0:?}")]
+    Character(char),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_uppercase_alphanumerics_of_any_length() {
+        for id in ["LJL44", "X", "2ZKJ9", "ABCDEFGH12"] {
+            assert_eq!(id.parse::<ListId>().unwrap().as_str(), id);
+        }
+    }
+
+    #[test]
+    fn rejects_empty_and_invalid_characters() {
+        assert_eq!("".parse::<ListId>(), Err(ListIdError::Empty));
+        assert_eq!("ljl44".parse::<ListId>(), Err(ListIdError::Character('l')));
+        assert_eq!("LJL-4".parse::<ListId>(), Err(ListIdError::Character('-')));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn ids_from_the_api_are_kept_as_is() {
+        assert_eq!(ListId::from_api("ljl-44").as_str(), "ljl-44");
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use reqwest::StatusCode;
+use std::fmt;
+use std::time::Duration;
+
+/// API endpoint a request was made to, passed to `MetricsSink` hooks
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Endpoint {
+    /// `Client::ranks`
+    Ranks,
+    /// `Client::list`
+    List,
+    /// `Client::list_date`
+    ListDate,
+    /// `Client::create_list`
+    CreateList,
+    /// HEAD request for the size of a list download
+    DownloadSize,
+    /// Download of a list's CSV
+    Download,
+}
+impl Endpoint {
+    /// Short name of the endpoint, suitable as a metric label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endpoint::Ranks => "ranks",
+            Endpoint::List => "list",
+            Endpoint::ListDate => "list_date",
+            Endpoint::CreateList => "create_list",
+            Endpoint::DownloadSize => "download_size",
+            Endpoint::Download => "download",
+        }
+    }
+}
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Hooks invoked by the client around each request, for bridging to a metrics library
+///
+/// All hooks default to doing nothing. Retried requests invoke `on_request` and `on_response` once
+/// per attempt.
+pub trait MetricsSink: Send + Sync {
+    /// A request is about to be sent
+    fn on_request(&self, endpoint: Endpoint) {
+        let _ = endpoint;
+    }
+    /// A request completed
+    ///
+    /// `status` is `None` if no response was received, and `latency` is the time until the
+    /// response headers arrived.
+    fn on_response(&self, endpoint: Endpoint, status: Option<StatusCode>, latency: Duration) {
+        let _ = (endpoint, status, latency);
+    }
+    /// A list download's body was read
+    fn on_bytes(&self, endpoint: Endpoint, bytes: u64) {
+        let _ = (endpoint, bytes);
+    }
+}
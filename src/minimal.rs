@@ -0,0 +1,235 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+//! Async client built directly on `hyper` and `rustls`, for builds that want a small HTTP stack
+//!
+//! Only the three GET endpoints and list downloads are offered, with none of the retries,
+//! caching, or hooks of `crate::Client`. Responses use the same types as the main client, and
+//! lists are parsed as they are read from the connection. Roots of trust are the Mozilla root
+//! certificates compiled into the binary, and only https URLs are requested, so redirects to
+//! plain http are refused. Download URLs and their redirects are checked against the same host
+//! allowlists as `crate::Client`.
+
+use crate::lines::LineSplitter;
+use crate::redirect::{
+    DEFAULT_DOWNLOAD_HOSTS, check_download_host, check_redirect_host, redirect_target,
+};
+use crate::{
+    API_BASE, ApiError, DEFAULT_MAX_PAGES, DEFAULT_MAX_REDIRECTS, DownloadListError, ListId,
+    ListsResponse, RankedDomain, RanksPage, RanksResponse, USER_AGENT, check_subdomains,
+    parse_line,
+};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::{Request, Response, StatusCode};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+/// Error returned by the `hyper` client
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Error making request: {0}")]
+    Request(#[from] hyper_util::client::legacy::Error),
+    #[error("Error reading response: {0}")]
+    Body(#[from] hyper::Error),
+    #[error("Error building request: {0}")]
+    Http(#[from] hyper::http::Error),
+    #[error("Unexpected HTTP status {0}")]
+    Status(StatusCode),
+    #[error("Error decoding JSON response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Api(#[from] ApiError),
+    #[error(transparent)]
+    DownloadList(#[from] DownloadListError),
+}
+
+/// Async client used to make Tranco API calls over `hyper`
+#[derive(Clone, Debug)]
+pub struct Client {
+    client: hyper_util::client::legacy::Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
+    base_url: String,
+    download_hosts: Option<Vec<String>>,
+    redirect_hosts: Option<Vec<String>>,
+}
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Client {
+    /// Constructor
+    pub fn new() -> Self {
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_only()
+            .enable_http1()
+            .build();
+        Self {
+            client: hyper_util::client::legacy::Client::builder(TokioExecutor::new())
+                .build(connector),
+            base_url: API_BASE.into(),
+            download_hosts: Some(
+                DEFAULT_DOWNLOAD_HOSTS
+                    .iter()
+                    .map(|host| host.to_string())
+                    .collect(),
+            ),
+            redirect_hosts: None,
+        }
+    }
+    /// Point the client at a different API base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+    /// Hosts the download URLs returned by the API may point to, see
+    /// `crate::ClientBuilder::download_hosts`
+    pub fn with_download_hosts(mut self, hosts: Option<Vec<String>>) -> Self {
+        self.download_hosts = hosts;
+        self
+    }
+    /// Hosts list downloads may be redirected to, see `crate::ClientBuilder::redirect_hosts`
+    pub fn with_redirect_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.redirect_hosts = Some(hosts);
+        self
+    }
+    /// Send a GET request, following redirects and failing on unsuccessful statuses
+    ///
+    /// Every redirect target is checked against `redirect_hosts`, if given.
+    async fn get(
+        &self,
+        url: &str,
+        redirect_hosts: Option<&[String]>,
+    ) -> Result<Response<Incoming>, Error> {
+        let mut url = Url::parse(url).map_err(ApiError::InvalidUrl)?;
+        for _ in 0..=DEFAULT_MAX_REDIRECTS {
+            let request = Request::get(url.as_str())
+                .header(header::USER_AGENT, HeaderValue::from_static(USER_AGENT))
+                .body(Empty::new())?;
+            let response = self.client.request(request).await?;
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok());
+            match location {
+                Some(location) if status.is_redirection() => {
+                    url = redirect_target(&url, location)?;
+                    check_redirect_host(&url, redirect_hosts)?;
+                }
+                _ if status.is_success() => return Ok(response),
+                _ => return Err(Error::Status(status)),
+            }
+        }
+        Err(DownloadListError::TooManyRedirects(DEFAULT_MAX_REDIRECTS).into())
+    }
+    /// Send a GET request and decode its JSON response
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let body = self
+            .get(url, None)
+            .await?
+            .into_body()
+            .collect()
+            .await?
+            .to_bytes();
+        Ok(serde_json::from_slice(&body)?)
+    }
+    /// List ranks for a domain, see `crate::Client::ranks`
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    pub async fn ranks(&self, domain: &str) -> Result<RanksResponse, Error> {
+        let mut url = Url::parse(&format!("{}/ranks/domain/{domain}", self.base_url))
+            .map_err(ApiError::InvalidUrl)?;
+        let mut ranks = Vec::new();
+        for _ in 0..DEFAULT_MAX_PAGES {
+            let page: RanksPage = self.get_json(url.as_str()).await?;
+            ranks.extend(page.ranks);
+            match page.next {
+                Some(next) => url = url.join(&next).map_err(ApiError::InvalidUrl)?,
+                None => return Ok(RanksResponse { ranks }),
+            }
+        }
+        Err(ApiError::TooManyPages(DEFAULT_MAX_PAGES).into())
+    }
+    /// Get the metadata of a list by id, see `crate::Client::list`
+    ///
+    /// # Parameters
+    /// * `id` - id of the list
+    pub async fn list(&self, id: &ListId) -> Result<ListsResponse, Error> {
+        let url = format!("{}/lists/id/{id}", self.base_url);
+        let response: ListsResponse = self.get_json(&url).await?;
+        if response.list_id() != id.as_ref() {
+            return Err(ApiError::MismatchedListId {
+                requested: id.clone(),
+                returned: response.list_id().into(),
+            }
+            .into());
+        }
+        Ok(response)
+    }
+    /// Get the metadata of the daily list for a date, see `crate::Client::list_date`
+    ///
+    /// # Parameters
+    /// * `year`, `month`, `day` - date of the list
+    /// * `subdomains` - whether the list should include subdomains, or `None` for the API default
+    pub async fn list_date(
+        &self,
+        year: u16,
+        month: u8,
+        day: u8,
+        subdomains: Option<bool>,
+    ) -> Result<ListsResponse, Error> {
+        let mut url = format!("{}/lists/date/{year:04}{month:02}{day:02}", self.base_url);
+        if let Some(subdomains) = subdomains {
+            url.push_str(&format!("?subdomains={subdomains}"));
+        }
+        let response = self.get_json(&url).await?;
+        check_subdomains(subdomains, &response)?;
+        Ok(response)
+    }
+    /// Download a list, parsing it as it is read
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub async fn download_list(
+        &self,
+        response: &ListsResponse,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        response.check_downloadable()?;
+        check_download_host(
+            &response.download,
+            &self.base_url,
+            self.download_hosts.as_deref(),
+        )?;
+        let mut body = self
+            .get(&response.download, self.redirect_hosts.as_deref())
+            .await?
+            .into_body();
+        let mut list = Vec::new();
+        let mut parse = |line: &str| {
+            list.push(parse_line(line)?);
+            Ok(())
+        };
+        let mut lines = LineSplitter::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(chunk) = frame?.into_data() {
+                lines.push(&chunk, &mut parse)?;
+            }
+        }
+        lines.finish(&mut parse)?;
+        Ok(list)
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use reqwest::RequestBuilder;
+use std::time::Duration;
+
+/// Options overriding the client's configuration for a single call, used with the
+/// `*_with_options` methods of `Client`
+///
+/// Anything left unset falls back to the client's configuration.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RequestOptions {
+    timeout: Option<Duration>,
+}
+impl RequestOptions {
+    /// Options leaving everything to the client's configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Timeout of each request made by the call, replacing `ClientBuilder::metadata_timeout` or
+    /// `ClientBuilder::download_timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    /// Apply the options to a request
+    pub(crate) fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_sets_timeout() {
+        let client = reqwest::Client::new();
+        let request = RequestOptions::new()
+            .timeout(Duration::from_secs(2))
+            .apply(client.get("https://tranco-list.eu/api/ranks/domain/google.com"))
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(2)));
+        let request = RequestOptions::new()
+            .apply(client.get("https://tranco-list.eu/api/ranks/domain/google.com"))
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), None);
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::normalize_domain;
+#[cfg(feature = "reqwest")]
+use crate::{ApiError, Client, RanksResponse};
+
+/// Reduce a hostname to its pay-level domain using the public suffix list
+///
+/// For example, `www.example.co.uk` becomes `example.co.uk`. The hostname is lowercased and a
+/// trailing dot is ignored.
+///
+/// # Returns
+/// `None` if the hostname has no pay-level domain, e.g. because it is a public suffix itself
+pub fn pay_level_domain(host: &str) -> Option<String> {
+    psl::domain_str(&normalize_domain(host)).map(str::to_owned)
+}
+
+#[cfg(feature = "reqwest")]
+impl Client {
+    /// List ranks for the pay-level domain of a hostname
+    ///
+    /// Tranco ranks pay-level domains, so looking up e.g. `www.example.com` directly finds
+    /// nothing. Hostnames without a pay-level domain are looked up as given.
+    ///
+    /// # Parameters
+    /// * `host` - hostname whose pay-level domain to query ranks for
+    ///
+    /// # Returns
+    /// The domain that was queried, and its ranks
+    pub async fn ranks_pld(&self, host: &str) -> Result<(String, RanksResponse), ApiError> {
+        let domain = pay_level_domain(host).unwrap_or_else(|| host.into());
+        let ranks = self.ranks(&domain).await?;
+        Ok((domain, ranks))
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Query for the daily list of a date, used with `Client::list_date_query`
+///
+/// Only the parameters that have been set are sent to the API.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ListDateQuery {
+    #[serde(skip)]
+    date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subdomains: Option<bool>,
+    #[serde(skip)]
+    variant: ListVariant,
+    #[serde(skip)]
+    extra_params: Vec<(String, String)>,
+}
+impl ListDateQuery {
+    /// Query for the daily list of `date`, with every parameter left to the API's default
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            subdomains: None,
+            variant: ListVariant::default(),
+            extra_params: Vec::new(),
+        }
+    }
+    /// Whether the list should include subdomains
+    pub fn subdomains(mut self, subdomains: bool) -> Self {
+        self.subdomains = Some(subdomains);
+        self
+    }
+    /// Whether the list should include subdomains, or `None` to leave it to the API's default
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn maybe_subdomains(mut self, subdomains: Option<bool>) -> Self {
+        self.subdomains = subdomains;
+        self
+    }
+    /// Whether the list should include subdomains, if set
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn subdomains_flag(&self) -> Option<bool> {
+        self.subdomains
+    }
+    /// Which variant of the list to download, defaults to `ListVariant::Top`
+    pub fn variant(mut self, variant: ListVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+    /// Variant of the requested list
+    pub fn list_variant(&self) -> ListVariant {
+        self.variant
+    }
+    /// Add a query parameter this crate doesn't support yet, URL-encoded when sent
+    pub fn extra_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_params.push((key.into(), value.into()));
+        self
+    }
+    /// Query parameters added with `extra_param`
+    pub fn extra_params(&self) -> &[(String, String)] {
+        &self.extra_params
+    }
+    /// Date of the requested list
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+}
+impl From<NaiveDate> for ListDateQuery {
+    fn from(date: NaiveDate) -> Self {
+        Self::new(date)
+    }
+}
+
+/// Which variant of a list is downloaded
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ListVariant {
+    /// The top million domains, which is what the API links to
+    #[default]
+    Top,
+    /// Every domain in the combined ranking, which covers several million domains
+    Full,
+}
+impl ListVariant {
+    /// Final path segment of the download URL for this variant
+    pub(crate) fn path_segment(&self) -> &'static str {
+        match self {
+            ListVariant::Top => "1000000",
+            ListVariant::Full => "full",
+        }
+    }
+}
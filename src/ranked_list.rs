@@ -0,0 +1,291 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::RankedDomain;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+
+/// A downloaded list of ranked domains, ordered by rank
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RankedList(Vec<RankedDomain>);
+
+impl RankedList {
+    /// Unwrap into the underlying vector
+    pub fn into_inner(self) -> Vec<RankedDomain> {
+        self.0
+    }
+    /// Compute summary statistics of the list in a single pass
+    ///
+    /// With the `psl` feature, each domain is also reduced to its pay-level domain, which makes
+    /// this noticeably slower on lists of a million entries.
+    pub fn stats(&self) -> ListStats {
+        #[cfg(feature = "psl")]
+        let mut plds = HashSet::new();
+        let mut stats = ListStats {
+            count: self.0.len(),
+            min_rank: None,
+            max_rank: None,
+            contiguous: true,
+            #[cfg(feature = "psl")]
+            distinct_plds: 0,
+        };
+        for (i, domain) in self.0.iter().enumerate() {
+            stats.min_rank = Some(
+                stats
+                    .min_rank
+                    .map_or(domain.rank, |min| min.min(domain.rank)),
+            );
+            stats.max_rank = Some(
+                stats
+                    .max_rank
+                    .map_or(domain.rank, |max| max.max(domain.rank)),
+            );
+            stats.contiguous &= domain.rank == i as u64 + 1;
+            #[cfg(feature = "psl")]
+            plds.insert(crate::pay_level_domain(&domain.domain).unwrap_or_else(|| {
+                domain
+                    .domain
+                    .trim()
+                    .trim_end_matches('.')
+                    .to_ascii_lowercase()
+            }));
+        }
+        #[cfg(feature = "psl")]
+        {
+            stats.distinct_plds = plds.len();
+        }
+        stats
+    }
+    /// Walk this list and `other` together by domain
+    ///
+    /// Yields `(domain, rank in self, rank in other)` for every domain in either list: first the
+    /// domains of this list in order, then those only in `other` in its order. Lookups go through
+    /// hash maps, so this is linear in the combined length.
+    pub fn outer_join<'a>(
+        &'a self,
+        other: &'a RankedList,
+    ) -> impl Iterator<Item = (&'a str, Option<u64>, Option<u64>)> + 'a {
+        let other_ranks: HashMap<&str, u64> = other
+            .0
+            .iter()
+            .map(|ranked| (ranked.domain.as_str(), ranked.rank))
+            .collect();
+        let own_domains: HashSet<&str> =
+            self.0.iter().map(|ranked| ranked.domain.as_str()).collect();
+        let both = self.0.iter().map(move |ranked| {
+            let domain = ranked.domain.as_str();
+            (domain, Some(ranked.rank), other_ranks.get(domain).copied())
+        });
+        let other_only = other
+            .0
+            .iter()
+            .filter(move |ranked| !own_domains.contains(ranked.domain.as_str()))
+            .map(|ranked| (ranked.domain.as_str(), None, Some(ranked.rank)));
+        both.chain(other_only)
+    }
+    /// Jaccard index of the domains of this list and `other`
+    ///
+    /// Computes `|A ∩ B| / |A ∪ B|` over the sets of domains, optionally restricted to the first
+    /// `top_n` entries of each list. Two empty sets are considered identical, giving 1.
+    pub fn jaccard(&self, other: &RankedList, top_n: Option<usize>) -> f64 {
+        fn domains(list: &RankedList, top_n: Option<usize>) -> HashSet<&str> {
+            let top_n = top_n.unwrap_or(list.0.len()).min(list.0.len());
+            list.0[..top_n]
+                .iter()
+                .map(|ranked| ranked.domain.as_str())
+                .collect()
+        }
+        let (a, b) = (domains(self, top_n), domains(other, top_n));
+        let intersection = a.intersection(&b).count();
+        let union = a.len() + b.len() - intersection;
+        if union == 0 {
+            1.0
+        } else {
+            intersection as f64 / union as f64
+        }
+    }
+    /// The top `percent` percent of the list
+    ///
+    /// `percent` is clamped to `[0, 100]` and the cutoff is rounded up, so any positive percentage
+    /// of a non-empty list contains at least one domain. NaN selects nothing.
+    pub fn top_percent(&self, percent: f64) -> &[RankedDomain] {
+        self.top_fraction(percent / 100.0)
+    }
+    /// The top `fraction` of the list
+    ///
+    /// `fraction` is clamped to `[0, 1]` and the cutoff is rounded up, so any positive fraction of
+    /// a non-empty list contains at least one domain. NaN selects nothing.
+    pub fn top_fraction(&self, fraction: f64) -> &[RankedDomain] {
+        if fraction.is_nan() {
+            return &[];
+        }
+        let cutoff = (self.0.len() as f64 * fraction.clamp(0.0, 1.0)).ceil() as usize;
+        &self.0[..cutoff.min(self.0.len())]
+    }
+}
+
+impl From<Vec<RankedDomain>> for RankedList {
+    fn from(domains: Vec<RankedDomain>) -> Self {
+        Self(domains)
+    }
+}
+
+impl From<RankedList> for Vec<RankedDomain> {
+    fn from(list: RankedList) -> Self {
+        list.0
+    }
+}
+
+impl FromIterator<RankedDomain> for RankedList {
+    fn from_iter<I: IntoIterator<Item = RankedDomain>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for RankedList {
+    type Item = RankedDomain;
+    type IntoIter = std::vec::IntoIter<RankedDomain>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Deref for RankedList {
+    type Target = [RankedDomain];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Summary statistics of a `RankedList`, from `RankedList::stats`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListStats {
+    /// Number of entries in the list
+    pub count: usize,
+    /// Lowest (best) rank, or `None` for an empty list
+    pub min_rank: Option<u64>,
+    /// Highest (worst) rank, or `None` for an empty list
+    pub max_rank: Option<u64>,
+    /// Whether the entries are ranked 1, 2, 3, ... in order with no gaps or repeats
+    ///
+    /// Vacuously true for an empty list.
+    pub contiguous: bool,
+    /// Number of distinct pay-level domains, as given by `pay_level_domain`
+    ///
+    /// Domains are compared after normalization, and a domain without a pay-level domain (i.e. a
+    /// public suffix) counts as its own. Lists generated with the pay-level domain filter have one
+    /// entry per pay-level domain, so this is only lower than `count` for lists that include
+    /// subdomains, where e.g. `www.example.com` and `mail.example.com` both count as
+    /// `example.com`.
+    #[cfg(feature = "psl")]
+    pub distinct_plds: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(domains: &[&str]) -> RankedList {
+        domains
+            .iter()
+            .enumerate()
+            .map(|(i, domain)| RankedDomain {
+                rank: i as u64 + 1,
+                domain: (*domain).into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outer_join_overlapping_and_disjoint() {
+        let a = list(&["google.com", "facebook.com", "amazon.com"]);
+        let b = list(&["amazon.com", "google.com", "apple.com"]);
+        let joined: Vec<_> = a.outer_join(&b).collect();
+        assert_eq!(
+            joined,
+            [
+                ("google.com", Some(1), Some(2)),
+                ("facebook.com", Some(2), None),
+                ("amazon.com", Some(3), Some(1)),
+                ("apple.com", None, Some(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn outer_join_empty() {
+        let a = list(&["google.com"]);
+        let empty = RankedList::default();
+        assert_eq!(
+            a.outer_join(&empty).collect::<Vec<_>>(),
+            [("google.com", Some(1), None)]
+        );
+        assert_eq!(
+            empty.outer_join(&a).collect::<Vec<_>>(),
+            [("google.com", None, Some(1))]
+        );
+    }
+
+    #[test]
+    fn jaccard_identical() {
+        let a = list(&["google.com", "facebook.com"]);
+        let b = list(&["facebook.com", "google.com"]);
+        assert_eq!(a.jaccard(&b, None), 1.0);
+    }
+
+    #[test]
+    fn jaccard_disjoint() {
+        let a = list(&["google.com", "facebook.com"]);
+        let b = list(&["amazon.com", "apple.com"]);
+        assert_eq!(a.jaccard(&b, None), 0.0);
+    }
+
+    #[test]
+    fn jaccard_partial_overlap() {
+        // {google, facebook, amazon} and {google, amazon, apple, netflix}: 2 shared out of 5
+        let a = list(&["google.com", "facebook.com", "amazon.com"]);
+        let b = list(&["google.com", "amazon.com", "apple.com", "netflix.com"]);
+        assert_eq!(a.jaccard(&b, None), 0.4);
+        assert_eq!(b.jaccard(&a, None), 0.4);
+    }
+
+    #[test]
+    fn jaccard_top_n() {
+        let a = list(&["google.com", "facebook.com", "amazon.com"]);
+        let b = list(&["google.com", "apple.com", "facebook.com"]);
+        // {google, facebook} and {google, apple}: 1 shared out of 3
+        assert_eq!(a.jaccard(&b, Some(2)), 1.0 / 3.0);
+        // {google, facebook, amazon} and {google, apple, facebook}: 2 shared out of 4
+        assert_eq!(a.jaccard(&b, Some(3)), 0.5);
+        // Longer than both lists
+        assert_eq!(a.jaccard(&b, Some(10)), 0.5);
+        assert_eq!(a.jaccard(&b, Some(0)), 1.0);
+    }
+
+    #[test]
+    fn jaccard_empty() {
+        let empty = RankedList::default();
+        assert_eq!(empty.jaccard(&empty, None), 1.0);
+        assert_eq!(empty.jaccard(&list(&["google.com"]), None), 0.0);
+    }
+
+    #[test]
+    fn stats() {
+        let stats = list(&["www.google.com", "mail.google.com", "bbc.co.uk", "co.uk"]).stats();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.min_rank, Some(1));
+        assert_eq!(stats.max_rank, Some(4));
+        assert!(stats.contiguous);
+        #[cfg(feature = "psl")]
+        assert_eq!(stats.distinct_plds, 3);
+    }
+}
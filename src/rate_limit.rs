@@ -0,0 +1,60 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::time::{self, Instant};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Token bucket shared by all the requests (or downloaded bytes) of a client
+///
+/// The bucket holds up to `tokens` tokens and refills one every `per / tokens`, so bursts of
+/// `tokens` are allowed but the long-run rate never exceeds `tokens` per `per`. It's tracked
+/// as the time the bucket will next be full, which is all the state a token bucket needs.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// Number of tokens refilled every `per`
+    tokens: u64,
+    /// Time to refill the whole bucket
+    per: Duration,
+    /// Time to refill the whole bucket less one token, i.e. how far ahead takers may borrow
+    burst: Duration,
+    /// When the bucket will be full again
+    full_at: Mutex<Instant>,
+}
+impl RateLimiter {
+    /// Allow `tokens` tokens per `per`, treating 0 as 1
+    pub(crate) fn new(tokens: u64, per: Duration) -> Self {
+        let tokens = tokens.max(1);
+        Self {
+            tokens,
+            per,
+            burst: per.saturating_sub(per.div_f64(tokens as f64)),
+            full_at: Mutex::new(Instant::now()),
+        }
+    }
+    /// Wait until a token is available and take it
+    pub(crate) async fn acquire(&self) {
+        self.acquire_n(1).await;
+    }
+    /// Wait until `n` tokens are available and take them
+    ///
+    /// Only one token has to be available, the rest are borrowed from the future, delaying the
+    /// next taker instead. This lets a chunk larger than the bucket through without deadlock.
+    pub(crate) async fn acquire_n(&self, n: u64) {
+        let ready_at = {
+            let mut full_at = self.full_at.lock().expect("rate limiter lock poisoned");
+            let now = Instant::now();
+            let start = (*full_at).max(now);
+            *full_at = start + self.per.mul_f64(n as f64 / self.tokens as f64);
+            start.checked_sub(self.burst).unwrap_or(now)
+        };
+        time::sleep(ready_at.saturating_duration_since(Instant::now())).await;
+    }
+}
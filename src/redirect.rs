@@ -0,0 +1,276 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::DownloadListError;
+#[cfg(feature = "reqwest")]
+use crate::{Client, Download, Endpoint};
+#[cfg(feature = "reqwest")]
+use reqwest::StatusCode;
+#[cfg(feature = "reqwest")]
+use reqwest::header::{AUTHORIZATION, COOKIE, HeaderMap, LOCATION, PROXY_AUTHORIZATION};
+use url::Url;
+
+/// Hosts the API's download URLs may point to by default, besides the API host itself
+pub(crate) const DEFAULT_DOWNLOAD_HOSTS: &[&str] = &["tranco-list.eu"];
+
+/// A redirect followed on the way to a list download
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedirectHop {
+    /// URL that was requested
+    pub url: Url,
+    /// Redirect status it answered with
+    pub status: StatusCode,
+}
+
+/// Where the bytes of a downloaded list came from
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadProvenance {
+    /// Redirects followed from the download URL given by the API, in order
+    pub redirects: Vec<RedirectHop>,
+    /// URL the list was finally downloaded from
+    pub url: Url,
+    /// Status of the final response
+    pub status: StatusCode,
+}
+#[cfg(feature = "reqwest")]
+impl DownloadProvenance {
+    /// Provenance of a response returned by `send_following_redirects`
+    pub(crate) fn of(response: &Download) -> Self {
+        // Browsers follow redirects themselves, without telling which
+        #[cfg(target_arch = "wasm32")]
+        let redirects = Vec::new();
+        #[cfg(not(target_arch = "wasm32"))]
+        let redirects = response
+            .extensions()
+            .get::<Redirects>()
+            .map(|redirects| redirects.0.clone())
+            .unwrap_or_default();
+        Self {
+            redirects,
+            url: response.url().clone(),
+            status: response.status(),
+        }
+    }
+}
+
+/// Redirects followed to reach a response, kept in its extensions
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+#[derive(Clone)]
+struct Redirects(Vec<RedirectHop>);
+
+#[cfg(feature = "reqwest")]
+impl Client {
+    /// Send a request for a list download URL, following redirects
+    ///
+    /// The first URL must be on one of the allowed download hosts, and every redirect target on
+    /// one of the allowed redirect hosts, if any are configured. The default transport doesn't
+    /// follow redirects itself; if a custom transport does, only the host it ends up on is
+    /// checked. The redirects followed are kept in the response for `DownloadProvenance::of`.
+    ///
+    /// Like with reqwest's own redirect policy, redirects to another origin than `url` are
+    /// followed without the `Authorization`, `Cookie`, and `Proxy-Authorization` headers,
+    /// including any added by the request hook.
+    ///
+    /// # Parameters
+    /// * `endpoint` - endpoint the requests are reported as
+    /// * `url` - URL to request first
+    /// * `request` - builds the request for a URL, called again for every redirect
+    pub(crate) async fn send_following_redirects(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        request: impl Fn(&str) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, DownloadListError> {
+        check_download_host(url, &self.base_url, self.download_hosts.as_deref())?;
+        let mut response = self.send(endpoint, request(url)).await?;
+        let requested = reqwest::Url::parse(url).ok();
+        if response.url().host_str() != requested.as_ref().and_then(|url| url.host_str()) {
+            check_redirect_host(response.url(), self.redirect_hosts.as_deref())?;
+        }
+        let mut redirects = Vec::new();
+        while is_redirect(response.status()) {
+            let Some(location) = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+            else {
+                break;
+            };
+            let target = response
+                .url()
+                .join(location)
+                .map_err(|_| DownloadListError::UnexpectedRedirectHost(location.into()))?;
+            check_redirect_host(&target, self.redirect_hosts.as_deref())?;
+            if redirects.len() >= self.max_redirects {
+                return Err(DownloadListError::TooManyRedirects(self.max_redirects));
+            }
+            redirects.push(RedirectHop {
+                url: response.url().clone(),
+                status: response.status(),
+            });
+            // Release the connection (and any request permit) before following the redirect
+            drop(response);
+            let cross_origin = requested.as_ref().map(Url::origin) != Some(target.origin());
+            response = self
+                .send_with_origin(endpoint, request(target.as_str()), cross_origin)
+                .await?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        response.extensions_mut().insert(Redirects(redirects));
+        Ok(response)
+    }
+}
+
+/// Check that a download URL returned by the API is on an allowed host
+///
+/// The host of the API base URL is always allowed, so that mirrors serving their own downloads
+/// work without further configuration. Hosts are compared case-insensitively, and `None` allows
+/// any host.
+pub(crate) fn check_download_host(
+    url: &str,
+    base_url: &str,
+    allowed: Option<&[String]>,
+) -> Result<(), DownloadListError> {
+    let Some(allowed) = allowed else {
+        return Ok(());
+    };
+    let url = Url::parse(url).map_err(|_| DownloadListError::UnexpectedDownloadHost(url.into()))?;
+    let host = url.host_str().unwrap_or_default();
+    let api_host = Url::parse(base_url).ok();
+    let api_host = api_host.as_ref().and_then(Url::host_str);
+    if api_host.is_some_and(|api_host| api_host.eq_ignore_ascii_case(host))
+        || allowed
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    {
+        Ok(())
+    } else {
+        Err(DownloadListError::UnexpectedDownloadHost(host.into()))
+    }
+}
+
+/// Check that a redirect target is on an allowed host, compared case-insensitively
+///
+/// `None` allows any host.
+pub(crate) fn check_redirect_host(
+    url: &Url,
+    allowed: Option<&[String]>,
+) -> Result<(), DownloadListError> {
+    let Some(allowed) = allowed else {
+        return Ok(());
+    };
+    let host = url.host_str().unwrap_or_default();
+    if allowed
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    {
+        Ok(())
+    } else {
+        Err(DownloadListError::UnexpectedRedirectHost(host.into()))
+    }
+}
+
+/// Resolve the `Location` of a redirect, refusing to leave https
+///
+/// Used by the clients that follow redirects themselves rather than through reqwest.
+#[cfg(any(feature = "ureq", feature = "minimal"))]
+pub(crate) fn redirect_target(url: &Url, location: &str) -> Result<Url, DownloadListError> {
+    let target = url
+        .join(location)
+        .map_err(|_| DownloadListError::UnexpectedRedirectHost(location.into()))?;
+    if target.scheme() != "https" {
+        return Err(DownloadListError::InsecureRedirect(target.into()));
+    }
+    Ok(target)
+}
+
+/// Remove the headers that carry credentials from a request
+#[cfg(feature = "reqwest")]
+pub(crate) fn strip_credentials(headers: &mut HeaderMap) {
+    for name in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION] {
+        headers.remove(name);
+    }
+}
+
+/// Whether a status code redirects to the `Location` header
+#[cfg(feature = "reqwest")]
+fn is_redirect(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(hosts: &[&str]) -> Vec<String> {
+        hosts.iter().map(|host| host.to_string()).collect()
+    }
+
+    #[test]
+    fn download_host_must_be_allowed() {
+        let allowed = hosts(DEFAULT_DOWNLOAD_HOSTS);
+        let base_url = "https://tranco-list.eu/api";
+        assert!(
+            check_download_host(
+                "https://Tranco-List.eu/download/ABCDE/1000000",
+                base_url,
+                Some(&allowed)
+            )
+            .is_ok()
+        );
+        assert!(matches!(
+            check_download_host("https://evil.example/list.csv", base_url, Some(&allowed)),
+            Err(DownloadListError::UnexpectedDownloadHost(host)) if host == "evil.example"
+        ));
+        assert!(check_download_host("https://evil.example/list.csv", base_url, None).is_ok());
+    }
+
+    #[test]
+    fn api_host_is_always_allowed() {
+        assert!(
+            check_download_host(
+                "https://mirror.example/download/ABCDE/1000000",
+                "https://mirror.example/api",
+                Some(&[])
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn redirect_host_must_be_allowed() {
+        let allowed = hosts(&["cdn.tranco-list.eu"]);
+        let target = Url::parse("https://cdn.tranco-list.eu/ABCDE.csv").unwrap();
+        assert!(check_redirect_host(&target, Some(&allowed)).is_ok());
+        let target = Url::parse("https://evil.example/ABCDE.csv").unwrap();
+        assert!(matches!(
+            check_redirect_host(&target, Some(&allowed)),
+            Err(DownloadListError::UnexpectedRedirectHost(host)) if host == "evil.example"
+        ));
+    }
+
+    #[cfg(any(feature = "ureq", feature = "minimal"))]
+    #[test]
+    fn redirect_stays_on_https() {
+        let url = Url::parse("https://tranco-list.eu/download/ABCDE/1000000").unwrap();
+        assert_eq!(
+            redirect_target(&url, "/download/ABCDE/full")
+                .unwrap()
+                .as_str(),
+            "https://tranco-list.eu/download/ABCDE/full"
+        );
+        assert!(matches!(
+            redirect_target(&url, "http://tranco-list.eu/download/ABCDE/full"),
+            Err(DownloadListError::InsecureRedirect(_))
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Client, DownloadListError, Endpoint, Error, ListsResponse, RankedDomain, parse_list};
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_RANGE, ETAG, IF_RANGE, RANGE};
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+impl Client {
+    /// Download a list to a file, resuming a previously interrupted download if possible
+    ///
+    /// While the download is in progress, the `ETag` of the remote file is kept next to it in
+    /// `<path>.etag`. If that file exists when this is called, only the missing bytes are
+    /// requested, and the server sends them only if the `ETag` still matches. Otherwise, or if
+    /// the server doesn't support range requests, the whole list is downloaded again. The CSV
+    /// is only parsed once the file is complete, so a resume boundary in the middle of a line is
+    /// harmless. The list is always requested uncompressed, so offsets into the file match the
+    /// server's.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    /// * `path` - file to write the CSV to
+    pub async fn download_list_resumable(
+        &self,
+        response: &ListsResponse,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<RankedDomain>, Error> {
+        let path = path.as_ref();
+        let etag_path = etag_path(path);
+        let offset = match fs::read_to_string(&etag_path).await {
+            Ok(etag) => match fs::metadata(path).await {
+                Ok(metadata) if metadata.len() > 0 => Some((metadata.len(), etag)),
+                _ => None,
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(DownloadListError::File(e).into()),
+        };
+        let mut download = match &offset {
+            Some((len, etag)) => {
+                let ranged = self
+                    .fetch_list_with(response, |request| {
+                        identity(request)
+                            .header(RANGE, format!("bytes={len}-"))
+                            .header(IF_RANGE, etag)
+                    })
+                    .await;
+                match ranged {
+                    Err(DownloadListError::Request(e))
+                        if e.status() == Some(StatusCode::RANGE_NOT_SATISFIABLE) =>
+                    {
+                        self.fetch_list_with(response, identity).await?
+                    }
+                    ranged => ranged?,
+                }
+            }
+            None => self.fetch_list_with(response, identity).await?,
+        };
+        let resumed = download.status() == StatusCode::PARTIAL_CONTENT
+            && offset.as_ref().is_some_and(|(len, _)| {
+                content_range_start(download.headers().get(CONTENT_RANGE)) == Some(*len)
+            });
+        if download.status() == StatusCode::PARTIAL_CONTENT && !resumed {
+            // The server sent a range we didn't ask for, start over without one
+            download = self.fetch_list_with(response, identity).await?;
+        }
+        // Only resumable if the server identifies the file with a strong ETag
+        match download
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .filter(|etag| !etag.starts_with("W/"))
+        {
+            Some(etag) => fs::write(&etag_path, etag).await,
+            None => remove_if_exists(&etag_path).await,
+        }
+        .map_err(DownloadListError::File)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(path)
+            .await
+            .map_err(DownloadListError::File)?;
+        let mut written = 0;
+        while let Some(chunk) = self.next_chunk(&mut download).await? {
+            file.write_all(&chunk)
+                .await
+                .map_err(DownloadListError::File)?;
+            written += chunk.len() as u64;
+        }
+        file.flush().await.map_err(DownloadListError::File)?;
+        self.record_bytes(Endpoint::Download, written);
+        remove_if_exists(&etag_path)
+            .await
+            .map_err(DownloadListError::File)?;
+        let csv_body = fs::read(path).await.map_err(DownloadListError::File)?;
+        Ok(parse_list(&csv_body[..])?)
+    }
+}
+
+/// Path of the file holding the `ETag` of an in-progress download
+fn etag_path(path: &Path) -> PathBuf {
+    let mut etag_path = OsString::from(path.as_os_str());
+    etag_path.push(".etag");
+    etag_path.into()
+}
+
+/// Remove a file, ignoring it not existing
+async fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// First byte of a `Content-Range: bytes <start>-<end>/<len>` header
+fn content_range_start(content_range: Option<&reqwest::header::HeaderValue>) -> Option<u64> {
+    content_range?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Ask for the file as stored, so that byte ranges refer to the same bytes as the local file
+fn identity(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    request.header(ACCEPT_ENCODING, "identity")
+}
@@ -0,0 +1,112 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::time::{SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// Policy for retrying requests that fail with a transient error
+///
+/// Transient errors are connection failures, timeouts, and 5xx gateway/availability responses.
+/// Only `GET` and `HEAD` requests, such as the ones behind `ranks`, `list` and the downloads, are
+/// retried. In particular, `create_list` is never retried, since that could queue the same list
+/// twice.
+/// The delay between attempts doubles each time, starting at `initial_backoff` and capped at
+/// `max_backoff`. With `jitter` set, each delay is drawn at random from the upper half of that
+/// range, so that clients failing together don't retry together.
+///
+/// Rate limited (429) responses are retried after the delay in their `Retry-After` header when
+/// `honor_retry_after` is set and that delay is no longer than `max_backoff`. Otherwise they fail
+/// with a `RateLimited` error carrying the delay, so the caller can schedule the retry.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_backoff: Duration,
+    /// Whether to randomize each delay
+    pub jitter: bool,
+    /// Whether to wait out and retry rate limited responses
+    pub honor_retry_after: bool,
+}
+impl RetryPolicy {
+    /// Retry up to `max_retries` times with the default backoff
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+    /// Never retry
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+    /// Delay before retry number `attempt` (starting at 0)
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        let mut state = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_nanos() as u64)
+            ^ u64::from(attempt);
+        let fraction = (crate::splitmix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64;
+        backoff.mul_f64(0.5 + fraction / 2.0)
+    }
+}
+/// Default implementation for RetryPolicy, which never retries
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            honor_retry_after: true,
+        }
+    }
+}
+
+/// Whether a request error is worth retrying
+pub(crate) fn is_transient(e: &reqwest::Error) -> bool {
+    if e.is_timeout() {
+        return true;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if e.is_connect() {
+        return true;
+    }
+    matches!(
+        e.status().map(|status| status.as_u16()),
+        Some(500 | 502 | 503 | 504)
+    )
+}
+
+/// Delay requested by a `Retry-After` header, given either in seconds or as an HTTP date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means the request can be retried right away
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,70 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    ApiError, Client, List, ListDateQuery, ListId, ListsResponse, RankedDomain, RanksResponse,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use futures_util::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures_util::future::LocalBoxFuture as BoxFuture;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Request to the Tranco API, for using the client as a `tower::Service`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TrancoRequest {
+    /// Ranks of a domain, see `Client::ranks`
+    Ranks(String),
+    /// Metadata of a list, see `Client::list`
+    List(ListId),
+    /// Metadata of the list for a date, see `Client::list_date_query`
+    ListDate(ListDateQuery),
+    /// Contents of a list, see `Client::download_list`
+    DownloadList(Box<ListsResponse>),
+}
+
+/// Response to a `TrancoRequest`, with the variant matching the request
+#[derive(Debug)]
+pub enum TrancoResponse {
+    Ranks(RanksResponse),
+    List(Box<List>),
+    ListDate(Box<ListsResponse>),
+    DownloadList(Vec<RankedDomain>),
+}
+
+/// The client is always ready, so concurrency limits and load shedding are left to layers.
+/// Each call works on a clone of the client, which shares its connection pool. On wasm32 the
+/// future isn't `Send`, as browser fetches are tied to the thread that started them.
+impl Service<TrancoRequest> for Client {
+    type Response = TrancoResponse;
+    type Error = ApiError;
+    type Future = BoxFuture<'static, Result<TrancoResponse, ApiError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), ApiError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: TrancoRequest) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move {
+            Ok(match request {
+                TrancoRequest::Ranks(domain) => TrancoResponse::Ranks(client.ranks(&domain).await?),
+                TrancoRequest::List(id) => TrancoResponse::List(Box::new(client.list(&id).await?)),
+                TrancoRequest::ListDate(query) => {
+                    TrancoResponse::ListDate(Box::new(client.list_date_query(&query).await?))
+                }
+                TrancoRequest::DownloadList(response) => {
+                    TrancoResponse::DownloadList(client.download_list(&response).await?)
+                }
+            })
+        })
+    }
+}
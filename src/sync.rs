@@ -0,0 +1,194 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+//! Synchronous client built on `ureq`, for tools that don't want an async runtime
+//!
+//! Unlike `blocking::Client`, no tokio runtime is created; requests are made on the calling
+//! thread. Responses use the same types as the async client, and lists are parsed as they are
+//! read from the connection. Download URLs and their redirects are checked against the same
+//! host allowlists as `crate::Client`, and redirects are only followed to https URLs.
+
+use crate::redirect::{
+    DEFAULT_DOWNLOAD_HOSTS, check_download_host, check_redirect_host, redirect_target,
+};
+use crate::{
+    API_BASE, ApiError, DEFAULT_MAX_PAGES, DEFAULT_MAX_REDIRECTS, DownloadListError, ListId,
+    ListsResponse, RankedDomain, RanksPage, RanksResponse, USER_AGENT, check_subdomains,
+    parse_list,
+};
+use std::io::BufReader;
+use ureq::http::header::LOCATION;
+
+/// Error returned by the `ureq` client
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Error making request: {0}")]
+    Request(#[from] ureq::Error),
+    #[error(transparent)]
+    Api(#[from] ApiError),
+    #[error(transparent)]
+    DownloadList(#[from] DownloadListError),
+}
+
+/// Synchronous client used to make Tranco API calls
+#[derive(Clone, Debug)]
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    download_hosts: Option<Vec<String>>,
+    redirect_hosts: Option<Vec<String>>,
+}
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl From<ureq::Agent> for Client {
+    fn from(agent: ureq::Agent) -> Self {
+        Self::from_agent(agent)
+    }
+}
+impl Client {
+    /// Constructor, identifying itself with `USER_AGENT`
+    pub fn new() -> Self {
+        let config = ureq::Agent::config_builder().user_agent(USER_AGENT).build();
+        Self::from_agent(config.into())
+    }
+    /// Constructor from an agent, e.g. one configured with timeouts or a proxy
+    pub fn from_agent(agent: ureq::Agent) -> Self {
+        Self {
+            agent,
+            base_url: API_BASE.into(),
+            download_hosts: Some(
+                DEFAULT_DOWNLOAD_HOSTS
+                    .iter()
+                    .map(|host| host.to_string())
+                    .collect(),
+            ),
+            redirect_hosts: None,
+        }
+    }
+    /// Point the client at a different API base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+    /// Hosts the download URLs returned by the API may point to, see
+    /// `crate::ClientBuilder::download_hosts`
+    pub fn with_download_hosts(mut self, hosts: Option<Vec<String>>) -> Self {
+        self.download_hosts = hosts;
+        self
+    }
+    /// Hosts list downloads may be redirected to, see `crate::ClientBuilder::redirect_hosts`
+    pub fn with_redirect_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.redirect_hosts = Some(hosts);
+        self
+    }
+    /// List ranks for a domain, see `crate::Client::ranks`
+    ///
+    /// # Parameters
+    /// * `domain` - domain for which to query ranks in the daily lists of (at least) the past 30 days
+    pub fn ranks(&self, domain: &str) -> Result<RanksResponse, Error> {
+        let mut url = url::Url::parse(&format!("{}/ranks/domain/{domain}", self.base_url))
+            .map_err(ApiError::InvalidUrl)?;
+        let mut ranks = Vec::new();
+        for _ in 0..DEFAULT_MAX_PAGES {
+            let page: RanksPage = self
+                .agent
+                .get(url.as_str())
+                .call()?
+                .body_mut()
+                .read_json()?;
+            ranks.extend(page.ranks);
+            match page.next {
+                Some(next) => url = url.join(&next).map_err(ApiError::InvalidUrl)?,
+                None => return Ok(RanksResponse { ranks }),
+            }
+        }
+        Err(ApiError::TooManyPages(DEFAULT_MAX_PAGES).into())
+    }
+    /// Get the metadata of a list by id, see `crate::Client::list`
+    ///
+    /// # Parameters
+    /// * `id` - id of the list
+    pub fn list(&self, id: &ListId) -> Result<ListsResponse, Error> {
+        let url = format!("{}/lists/id/{id}", self.base_url);
+        let response: ListsResponse = self.agent.get(&url).call()?.body_mut().read_json()?;
+        if response.list_id() != id.as_ref() {
+            return Err(ApiError::MismatchedListId {
+                requested: id.clone(),
+                returned: response.list_id().into(),
+            }
+            .into());
+        }
+        Ok(response)
+    }
+    /// Get the metadata of the daily list for a date, see `crate::Client::list_date`
+    ///
+    /// # Parameters
+    /// * `year`, `month`, `day` - date of the list
+    /// * `subdomains` - whether the list should include subdomains, or `None` for the API default
+    pub fn list_date(
+        &self,
+        year: u16,
+        month: u8,
+        day: u8,
+        subdomains: Option<bool>,
+    ) -> Result<ListsResponse, Error> {
+        let url = format!("{}/lists/date/{year:04}{month:02}{day:02}", self.base_url);
+        let mut request = self.agent.get(&url);
+        if let Some(subdomains) = subdomains {
+            request = request.query("subdomains", subdomains.to_string());
+        }
+        let response = request.call()?.body_mut().read_json()?;
+        check_subdomains(subdomains, &response)?;
+        Ok(response)
+    }
+    /// Download a list, parsing it as it is read
+    ///
+    /// Redirects are followed here rather than by the agent, so that each target can be checked
+    /// against `redirect_hosts`. Only https targets are followed, up to 10 redirects.
+    ///
+    /// # Parameters
+    /// * `list` - ListsResponse from either `list` or `list_date`
+    pub fn download_list(&self, response: &ListsResponse) -> Result<Vec<RankedDomain>, Error> {
+        response.check_downloadable()?;
+        check_download_host(
+            &response.download,
+            &self.base_url,
+            self.download_hosts.as_deref(),
+        )?;
+        let mut url = url::Url::parse(&response.download).map_err(ApiError::InvalidUrl)?;
+        for _ in 0..=DEFAULT_MAX_REDIRECTS {
+            let download = self
+                .agent
+                .get(url.as_str())
+                .config()
+                .max_redirects(0)
+                .build()
+                .call()?;
+            let location = download
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok());
+            match location {
+                Some(location) if download.status().is_redirection() => {
+                    url = redirect_target(&url, location)?;
+                    check_redirect_host(&url, self.redirect_hosts.as_deref())?;
+                }
+                _ => {
+                    let reader = BufReader::new(download.into_body().into_reader());
+                    return Ok(parse_list(reader)?);
+                }
+            }
+        }
+        Err(DownloadListError::TooManyRedirects(DEFAULT_MAX_REDIRECTS).into())
+    }
+}
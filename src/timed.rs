@@ -0,0 +1,47 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::time::Instant;
+use crate::{ApiError, Client, List, ListId, RanksResponse};
+use std::future::Future;
+use std::time::Duration;
+
+impl Client {
+    /// Like `ranks`, also returning the wall-clock time the call took
+    ///
+    /// The time includes every page and retry. For continuous monitoring, a `MetricsSink`
+    /// reports the latency of each individual request instead.
+    pub async fn ranks_timed(&self, domain: &str) -> Result<(RanksResponse, Duration), ApiError> {
+        timed(self.ranks(domain)).await
+    }
+    /// Like `list`, also returning the wall-clock time the call took
+    pub async fn list_timed(&self, id: &ListId) -> Result<(List, Duration), ApiError> {
+        timed(self.list(id)).await
+    }
+    /// Like `list_date`, also returning the wall-clock time the call took
+    pub async fn list_date_timed(
+        &self,
+        year: u16,
+        month: u8,
+        day: u8,
+        subdomains: Option<bool>,
+    ) -> Result<(List, Duration), ApiError> {
+        timed(self.list_date(year, month, day, subdomains)).await
+    }
+}
+
+/// Measure how long a call takes to complete
+async fn timed<T>(
+    call: impl Future<Output = Result<T, ApiError>>,
+) -> Result<(T, Duration), ApiError> {
+    let start = Instant::now();
+    let value = call.await?;
+    Ok((value, start.elapsed()))
+}
@@ -0,0 +1,257 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Client, SendError};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::BodyExt;
+use http_body_util::combinators::BoxBody;
+use reqwest::ResponseBuilderExt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Error returned by an `HttpTransport`
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Sends the requests built by the client, for routing them through something other than
+/// `reqwest`, e.g. an auditing or egress-controlled HTTP client
+///
+/// Requests and responses are `http` types with a `TransportBody`, so a transport doesn't need
+/// to know about `reqwest` at all. Transports should honor the `RequestTimeout` found in the
+/// request's extensions, which carries the client's per-endpoint timeouts. Retries, redirects,
+/// and status checks are handled by the client on top of the transport.
+///
+/// `reqwest::Client` implements this trait, and is the default transport.
+pub trait HttpTransport: Send + Sync {
+    /// Send a request and wait for the response headers
+    fn execute(
+        &self,
+        request: http::Request<TransportBody>,
+    ) -> BoxFuture<'_, Result<http::Response<TransportBody>, TransportError>>;
+}
+
+/// Timeout for a whole request, from sending it to reading the end of the response body
+///
+/// Set in the extensions of every request handed to an `HttpTransport`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestTimeout(pub Duration);
+
+/// Body of the requests and responses going through an `HttpTransport`
+pub struct TransportBody(BoxBody<Bytes, TransportError>);
+impl TransportBody {
+    /// Wrap any `http_body::Body`, e.g. the response body of another HTTP client
+    pub fn new<B>(body: B) -> Self
+    where
+        B: Body + Send + Sync + 'static,
+        B::Data: Into<Bytes>,
+        B::Error: Into<TransportError>,
+    {
+        Self(
+            body.map_frame(|frame| frame.map_data(Into::into))
+                .map_err(Into::into)
+                .boxed(),
+        )
+    }
+    /// Body with no data
+    pub fn empty() -> Self {
+        Self::new(http_body_util::Empty::<Bytes>::new())
+    }
+}
+impl Default for TransportBody {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+impl From<Bytes> for TransportBody {
+    fn from(bytes: Bytes) -> Self {
+        Self::new(http_body_util::Full::new(bytes))
+    }
+}
+impl From<Vec<u8>> for TransportBody {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes::from(bytes).into()
+    }
+}
+impl From<String> for TransportBody {
+    fn from(text: String) -> Self {
+        Bytes::from(text).into()
+    }
+}
+impl Body for TransportBody {
+    type Data = Bytes;
+    type Error = TransportError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, TransportError>>> {
+        Pin::new(&mut self.0).poll_frame(cx)
+    }
+    fn is_end_stream(&self) -> bool {
+        self.0.is_end_stream()
+    }
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint()
+    }
+}
+impl std::fmt::Debug for TransportBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TransportBody").finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Send a built request through the client's transport
+    pub(crate) async fn execute(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, SendError> {
+        let url = request.url().clone();
+        let response = self
+            .transport
+            .execute(into_transport_request(request)?)
+            .await?;
+        Ok(from_transport_response(response, url))
+    }
+}
+
+/// Convert a request built by the client for an `HttpTransport`
+fn into_transport_request(
+    request: reqwest::Request,
+) -> Result<http::Request<TransportBody>, reqwest::Error> {
+    let timeout = request.timeout().copied();
+    let request = http::Request::<reqwest::Body>::try_from(request)?;
+    let mut request = request.map(TransportBody::new);
+    if let Some(timeout) = timeout {
+        request.extensions_mut().insert(RequestTimeout(timeout));
+    }
+    Ok(request)
+}
+
+/// Convert the response of an `HttpTransport` back for the client, which reports `url` as the
+/// URL it came from
+fn from_transport_response(
+    response: http::Response<TransportBody>,
+    url: reqwest::Url,
+) -> reqwest::Response {
+    let (mut parts, body) = response.into_parts();
+    let carrier = http::Response::builder().url(url).body(());
+    if let Ok(carrier) = carrier {
+        parts.extensions.extend(carrier.into_parts().0.extensions);
+    }
+    reqwest::Response::from(http::Response::from_parts(parts, reqwest::Body::wrap(body)))
+}
+
+/// Convert a request handed to a transport back into a `reqwest::Request`, keeping its timeout
+fn into_reqwest_request(
+    request: http::Request<TransportBody>,
+) -> Result<reqwest::Request, reqwest::Error> {
+    let timeout = request.extensions().get::<RequestTimeout>().copied();
+    let mut request = reqwest::Request::try_from(request.map(reqwest::Body::wrap))?;
+    *request.timeout_mut() = timeout.map(|RequestTimeout(timeout)| timeout);
+    Ok(request)
+}
+
+impl HttpTransport for reqwest::Client {
+    fn execute(
+        &self,
+        request: http::Request<TransportBody>,
+    ) -> BoxFuture<'_, Result<http::Response<TransportBody>, TransportError>> {
+        Box::pin(async move {
+            let request = into_reqwest_request(request)?;
+            let response = reqwest::Client::execute(self, request).await?;
+            Ok(http::Response::from(response).map(TransportBody::new))
+        })
+    }
+}
+
+#[cfg(feature = "middleware")]
+impl HttpTransport for reqwest_middleware::ClientWithMiddleware {
+    fn execute(
+        &self,
+        request: http::Request<TransportBody>,
+    ) -> BoxFuture<'_, Result<http::Response<TransportBody>, TransportError>> {
+        Box::pin(async move {
+            let request = into_reqwest_request(request)?;
+            let response = reqwest_middleware::ClientWithMiddleware::execute(self, request)
+                .await
+                .map_err(|e| match e {
+                    // Unwrapped so that status checks and retries still see the reqwest error
+                    reqwest_middleware::Error::Reqwest(e) => TransportError::from(e),
+                    e => e.into(),
+                })?;
+            Ok(http::Response::from(response).map(TransportBody::new))
+        })
+    }
+}
+
+// Lets a transport be kept around after handing it to the builder, e.g. to inspect the
+// requests a fault-injecting transport saw
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    fn execute(
+        &self,
+        request: http::Request<TransportBody>,
+    ) -> BoxFuture<'_, Result<http::Response<TransportBody>, TransportError>> {
+        (**self).execute(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use std::sync::Mutex;
+
+    /// Answers every request with the same ranks page, remembering what it was sent
+    #[derive(Default)]
+    struct FakeTransport {
+        requests: Mutex<Vec<(http::Uri, Option<RequestTimeout>)>>,
+    }
+    impl HttpTransport for FakeTransport {
+        fn execute(
+            &self,
+            request: http::Request<TransportBody>,
+        ) -> BoxFuture<'_, Result<http::Response<TransportBody>, TransportError>> {
+            self.requests.lock().unwrap().push((
+                request.uri().clone(),
+                request.extensions().get::<RequestTimeout>().copied(),
+            ));
+            Box::pin(async {
+                Ok(http::Response::builder()
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(r#"{"ranks":[{"date":"2024-01-01","rank":5}]}"#.to_owned().into())?)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn client_sends_through_http_transport() {
+        let transport = Arc::new(FakeTransport::default());
+        let client = Client::builder()
+            .http_transport(Arc::clone(&transport))
+            .metadata_timeout(Some(Duration::from_secs(7)))
+            .build();
+        let ranks = client.ranks("example.com").await.unwrap();
+        assert_eq!(ranks.ranks[0].rank, 5);
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(
+            *requests,
+            [(
+                "https://tranco-list.eu/api/ranks/domain/example.com"
+                    .parse()
+                    .unwrap(),
+                Some(RequestTimeout(Duration::from_secs(7)))
+            )]
+        );
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2024 witchof0x20
+//
+// This file is part of tranco-rs.
+//
+// tranco-rs is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// tranco-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with tranco-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::time;
+use crate::{ApiError, Client, ListId, ListsResponse};
+use std::time::Duration;
+
+/// Delay before the first re-query of a list that isn't available yet
+const INITIAL_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Upper bound on the delay between re-queries
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+impl Client {
+    /// Wait until a list is available, re-querying its metadata until it is
+    ///
+    /// The delay between queries starts at 5 seconds and doubles each time, and is multiplied by
+    /// one more than the number of jobs ahead of the list in the queue, up to 5 minutes. A list
+    /// that is already available is returned without querying anything. Wrap the call in
+    /// `tokio::time::timeout` to give up after a while.
+    ///
+    /// # Parameters
+    /// * `response` - ListsResponse from `list`, `list_date`, or `create_list`
+    ///
+    /// # Returns
+    /// The metadata of the available list, or `ApiError::ListFailed` if generating it failed
+    pub async fn wait_for_list(&self, response: &ListsResponse) -> Result<ListsResponse, ApiError> {
+        let id = ListId::from_api(response.list_id());
+        let mut response = response.clone();
+        let mut attempt = 0;
+        loop {
+            if response.failed() {
+                return Err(ApiError::ListFailed(id));
+            }
+            if response.available() {
+                return Ok(response);
+            }
+            let backoff = INITIAL_POLL_INTERVAL.saturating_mul(2u32.saturating_pow(attempt));
+            let jobs = response.jobs_ahead().unwrap_or(0).saturating_add(1);
+            let delay = backoff
+                .saturating_mul(u32::try_from(jobs).unwrap_or(u32::MAX))
+                .min(MAX_POLL_INTERVAL);
+            time::sleep(delay).await;
+            response = self.list(&id).await?.into_metadata();
+            attempt += 1;
+        }
+    }
+}